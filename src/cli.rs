@@ -11,4 +11,42 @@ pub struct Cli {
     pub output_dir: PathBuf,
     #[structopt(parse(from_os_str))]
     pub output_file: PathBuf,
+    /// TOML configuration file
+    #[structopt(long, parse(from_os_str))]
+    pub config: Option<PathBuf>,
+    /// Preprocessor symbol to consider defined, can be repeated
+    #[structopt(short = "D", long = "define")]
+    pub defines: Vec<String>,
+    /// URL template for links to the source, e.g. `https://host/repo/blob/{rev}/{file}#L{line}`
+    #[structopt(long)]
+    pub source_link: Option<String>,
+    /// Revision to fill into `{rev}` of the source link
+    #[structopt(long = "rev")]
+    pub revision: Option<String>,
+    /// Template variable as `name=value`, e.g. `system_name=Inventory`, can be repeated
+    #[structopt(long = "var", parse(try_from_str = parse_var))]
+    pub vars: Vec<(String, String)>,
+}
+
+// `check-template <template_file>` checks a template without generating documentation.
+#[derive(StructOpt)]
+#[structopt(name = "check-template")]
+pub struct CheckTemplate {
+    #[structopt(parse(from_os_str))]
+    pub template_file: PathBuf,
+    /// TOML configuration file
+    #[structopt(long, parse(from_os_str))]
+    pub config: Option<PathBuf>,
+    /// Template variable as `name=value`, e.g. `system_name=Inventory`, can be repeated
+    #[structopt(long = "var", parse(try_from_str = parse_var))]
+    pub vars: Vec<(String, String)>,
+}
+
+fn parse_var(text: &str) -> Result<(String, String), String> {
+    match text.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected `name=value`, found `{}`", text)),
+    }
 }
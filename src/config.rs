@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::parser::SourceSpan;
+
+// Settings read from a TOML configuration file, e.g.
+//
+// defines = ["RELEASE", "UNITY_EDITOR"]
+// source_link = "https://github.com/org/repo/blob/{rev}/{file}#L{line}"
+//
+// [vars]
+// system_name = "Inventory"
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Config {
+    // Preprocessor symbols to consider defined
+    pub defines: Vec<String>,
+    // URL template for `[source_link]` with `{rev}`, `{file}`, `{line}` and `{end_line}`
+    pub source_link: Option<String>,
+    // Filled into `{rev}`, `HEAD` if not given
+    pub revision: Option<String>,
+    // Values of the `{{vars.<name>}}` template variables
+    pub vars: BTreeMap<String, String>,
+}
+
+// Environment variables such as `DOCGEN_VAR_SYSTEM_NAME` set the `system_name` template variable.
+pub const VAR_ENV_PREFIX: &str = "DOCGEN_VAR_";
+
+impl Config {
+    pub fn source_link(&self, span: &SourceSpan) -> Option<String> {
+        let template = self.source_link.as_ref()?;
+        Some(
+            template
+                .replace("{rev}", self.revision.as_deref().unwrap_or("HEAD"))
+                .replace("{file}", &span_path(span))
+                .replace("{line}", &span.start_line.to_string())
+                .replace("{end_line}", &span.end_line.to_string()),
+        )
+    }
+}
+
+// The file of a span with forward slashes, as used in URLs.
+pub fn span_path(span: &SourceSpan) -> String {
+    span.file
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// Picks the template variables out of environment variables, e.g. `std::env::vars()`.
+pub fn vars_from_env(env: impl IntoIterator<Item = (String, String)>) -> Vec<(String, String)> {
+    env.into_iter()
+        .filter_map(|(key, value)| {
+            let name = key.strip_prefix(VAR_ENV_PREFIX)?;
+            Some((name.to_lowercase(), value))
+        })
+        .collect()
+}
+
+pub fn load_config(config_file: &Path) -> Result<Config, io::Error> {
+    let content = fs::read_to_string(config_file)?;
+    toml::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_load_config() {
+        let test_dir = PathBuf::from("_load_config_test_data");
+        fs::create_dir_all(&test_dir).unwrap();
+        let valid = test_dir.join("valid.toml");
+        let invalid = test_dir.join("invalid.toml");
+        fs::write(
+            &valid,
+            "defines = [\"DEBUG\", \"UNITY_EDITOR\"]\nsource_link = \"https://host/{rev}/{file}#L{line}\"\n\n[vars]\nsystem_name = \"Inventory\"\n",
+        )
+        .unwrap();
+        fs::write(&invalid, "defines = \"DEBUG\"\n").unwrap();
+
+        let config = load_config(&valid).unwrap();
+        assert_eq!(config.defines, vec!["DEBUG", "UNITY_EDITOR"]);
+        assert_eq!(config.revision, None);
+        assert_eq!(config.vars["system_name"], "Inventory");
+        let span = SourceSpan {
+            file: PathBuf::from("src").join("Logger.cs"),
+            start_line: 12,
+            start_column: 5,
+            end_line: 40,
+            end_column: 5,
+        };
+        assert_eq!(
+            config.source_link(&span),
+            Some("https://host/HEAD/src/Logger.cs#L12".to_string())
+        );
+        let error = load_config(&invalid).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(load_config(&test_dir.join("missing.toml")).is_err());
+        fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[test]
+    fn test_vars_from_env() {
+        let env = [
+            ("DOCGEN_VAR_SYSTEM_NAME", "Inventory"),
+            ("PATH", "/usr/bin"),
+            ("DOCGEN_VARIABLE", "x"),
+        ]
+        .map(|(key, value)| (key.to_string(), value.to_string()));
+        assert_eq!(
+            vars_from_env(env),
+            vec![("system_name".to_string(), "Inventory".to_string())]
+        );
+    }
+}
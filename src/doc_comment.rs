@@ -0,0 +1,461 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum DocNode {
+    Text(String),
+    Element {
+        name: String,
+        attributes: Vec<(String, String)>,
+        children: Vec<DocNode>,
+    },
+}
+
+impl DocNode {
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        match self {
+            DocNode::Element { attributes, .. } => attributes
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.as_str()),
+            DocNode::Text(_) => None,
+        }
+    }
+}
+
+pub type DocText = Vec<DocNode>;
+
+// A documented entry that is identified by a name, such as a `<param>` or an `<exception>`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DocEntry {
+    pub name: String,
+    pub text: DocText,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DocComment {
+    pub summary: Option<DocText>,
+    pub remarks: Option<DocText>,
+    pub example: Option<DocText>,
+    pub returns: Option<DocText>,
+    pub value: Option<DocText>,
+    pub params: Vec<DocEntry>,
+    pub type_params: Vec<DocEntry>,
+    pub exceptions: Vec<DocEntry>,
+    pub inheritdoc: Option<InheritDoc>,
+}
+
+// An `<inheritdoc/>` tag, optionally naming the member to inherit from with `cref`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InheritDoc {
+    pub cref: Option<String>,
+}
+
+impl DocComment {
+    pub fn param(&self, name: &str) -> Option<&DocEntry> {
+        self.params.iter().find(|p| p.name == name)
+    }
+
+    // Combines inherited documentation with this one: sections written here take precedence.
+    pub fn inherit_from(&self, inherited: &DocComment) -> DocComment {
+        fn pick(own: &Option<DocText>, inherited: &Option<DocText>) -> Option<DocText> {
+            own.clone().or_else(|| inherited.clone())
+        }
+        fn entries(own: &[DocEntry], inherited: &[DocEntry]) -> Vec<DocEntry> {
+            let mut entries = own.to_vec();
+            for entry in inherited {
+                if !entries.iter().any(|e| e.name == entry.name) {
+                    entries.push(entry.clone());
+                }
+            }
+            entries
+        }
+        DocComment {
+            summary: pick(&self.summary, &inherited.summary),
+            remarks: pick(&self.remarks, &inherited.remarks),
+            example: pick(&self.example, &inherited.example),
+            returns: pick(&self.returns, &inherited.returns),
+            value: pick(&self.value, &inherited.value),
+            params: entries(&self.params, &inherited.params),
+            type_params: entries(&self.type_params, &inherited.type_params),
+            exceptions: entries(&self.exceptions, &inherited.exceptions),
+            inheritdoc: None,
+        }
+    }
+}
+
+// Parses the lines of a `///` (or `/** */`) documentation comment into its sections.
+pub fn parse_doc_comment(lines: &[String]) -> Option<DocComment> {
+    let content = lines
+        .iter()
+        .flat_map(|line| strip_comment_markers(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let nodes = XmlParser::new(&content).parse_nodes(None);
+
+    let mut doc = DocComment::default();
+    let mut loose_text = Vec::new();
+    for node in nodes {
+        let DocNode::Element { name, children, .. } = &node else {
+            loose_text.push(node);
+            continue;
+        };
+        let children = children.clone();
+        match name.as_str() {
+            "summary" => append_section(&mut doc.summary, children),
+            "remarks" => append_section(&mut doc.remarks, children),
+            "example" => append_section(&mut doc.example, children),
+            "returns" => append_section(&mut doc.returns, children),
+            "value" => append_section(&mut doc.value, children),
+            "inheritdoc" => {
+                doc.inheritdoc = Some(InheritDoc {
+                    cref: node.attribute("cref").map(String::from),
+                })
+            }
+            "param" | "typeparam" | "exception" => {
+                let key = if name == "exception" { "cref" } else { "name" };
+                let entry = DocEntry {
+                    name: node.attribute(key).unwrap_or_default().to_string(),
+                    text: children,
+                };
+                match name.as_str() {
+                    "param" => doc.params.push(entry),
+                    "typeparam" => doc.type_params.push(entry),
+                    _ => doc.exceptions.push(entry),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Text written without any tags is treated as the summary
+    if doc.summary.is_none() && !plain_text(&loose_text).is_empty() {
+        doc.summary = Some(loose_text);
+    }
+
+    (doc != DocComment::default()).then_some(doc)
+}
+
+fn append_section(section: &mut Option<DocText>, children: DocText) {
+    match section {
+        Some(existing) => {
+            existing.push(DocNode::Text(" ".to_string()));
+            existing.extend(children);
+        }
+        None => *section = Some(children),
+    }
+}
+
+fn strip_comment_markers(line: &str) -> Vec<&str> {
+    if let Some(rest) = line.strip_prefix("///") {
+        return vec![rest.strip_prefix(' ').unwrap_or(rest)];
+    }
+    let block = line.strip_prefix("/**").unwrap_or(line);
+    let block = block.strip_suffix("*/").unwrap_or(block);
+    block
+        .lines()
+        .map(|l| {
+            let l = l.trim_start();
+            let l = l.strip_prefix('*').unwrap_or(l);
+            l.strip_prefix(' ').unwrap_or(l)
+        })
+        .collect()
+}
+
+// Concatenates the text of the nodes, collapsing whitespace.
+pub fn plain_text(nodes: &[DocNode]) -> String {
+    fn collect(nodes: &[DocNode], out: &mut String) {
+        for node in nodes {
+            match node {
+                DocNode::Text(text) => out.push_str(text),
+                DocNode::Element { children, .. } => collect(children, out),
+            }
+        }
+    }
+    let mut text = String::new();
+    collect(nodes, &mut text);
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Removes the kind prefix and parameter list from a `cref`, e.g. `M:A.B(System.Int32)` becomes `A.B`.
+pub fn strip_cref(cref: &str) -> &str {
+    let name = match cref.as_bytes().get(1) {
+        Some(b':') => &cref[2..],
+        _ => cref,
+    };
+    name.split('(').next().unwrap_or(name).trim()
+}
+
+// Removes generic arguments from a `cref`: `List{T}` and ``List`1`` both become `List`.
+pub fn strip_generic_arity(name: &str) -> String {
+    let mut result = String::new();
+    let mut depth = 0;
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '<' => depth += 1,
+            '}' | '>' => depth -= 1,
+            '`' => {
+                while chars
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_digit() || *c == '`')
+                {
+                    chars.next();
+                }
+            }
+            _ if depth == 0 => result.push(c),
+            _ => {}
+        }
+    }
+    result
+}
+
+// A forgiving parser for the XML fragments found in documentation comments. Stray `<` characters
+// are kept as text, unknown closing tags are ignored and unclosed elements end with their parent.
+struct XmlParser<'a> {
+    input: &'a str,
+    pos: usize,
+    open_elements: Vec<String>,
+}
+
+impl<'a> XmlParser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            pos: 0,
+            open_elements: Vec::new(),
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn parse_nodes(&mut self, parent: Option<&str>) -> Vec<DocNode> {
+        let mut nodes = Vec::new();
+        let mut text = String::new();
+        while !self.rest().is_empty() {
+            let rest = self.rest();
+            if rest.starts_with("<!--") {
+                self.pos += rest.find("-->").map_or(rest.len(), |end| end + 3);
+            } else if let Some(closing) = rest.strip_prefix("</") {
+                let end = closing.find('>').map_or(closing.len(), |end| end + 1);
+                let name = closing[..end].trim_end_matches('>').trim();
+                if Some(name) == parent {
+                    self.pos += 2 + end;
+                    break;
+                }
+                if self.open_elements.iter().any(|open| open == name) {
+                    // Closes an ancestor, so this element ends here
+                    break;
+                }
+                self.pos += 2 + end;
+            } else if let Some(element) = self.parse_element() {
+                if !text.is_empty() {
+                    nodes.push(DocNode::Text(decode_entities(&std::mem::take(&mut text))));
+                }
+                nodes.push(element);
+            } else {
+                let c = rest.chars().next().unwrap();
+                text.push(c);
+                self.pos += c.len_utf8();
+            }
+        }
+        if !text.is_empty() {
+            nodes.push(DocNode::Text(decode_entities(&text)));
+        }
+        nodes
+    }
+
+    fn parse_element(&mut self) -> Option<DocNode> {
+        let rest = self.rest().strip_prefix('<')?;
+        let name_len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-' || c == ':'))
+            .unwrap_or(rest.len());
+        if name_len == 0 || !rest.starts_with(|c: char| c.is_alphabetic()) {
+            return None;
+        }
+        let name = rest[..name_len].to_string();
+        let tag_end = rest.find('>')?;
+        let tag = &rest[name_len..tag_end];
+        let self_closing = tag.trim_end().ends_with('/');
+        let attributes = parse_attributes(tag.trim_end().trim_end_matches('/'));
+        self.pos += 1 + tag_end + 1;
+
+        let children = if self_closing {
+            Vec::new()
+        } else {
+            self.open_elements.push(name.clone());
+            let children = self.parse_nodes(Some(&name));
+            self.open_elements.pop();
+            children
+        };
+        Some(DocNode::Element {
+            name,
+            attributes,
+            children,
+        })
+    }
+}
+
+fn parse_attributes(tag: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut rest = tag.trim_start();
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].trim().to_string();
+        let value_part = rest[eq + 1..].trim_start();
+        let Some(quote) = value_part
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+        else {
+            break;
+        };
+        let value_end = value_part[1..]
+            .find(quote)
+            .map_or(value_part.len(), |e| e + 1);
+        attributes.push((key, decode_entities(&value_part[1..value_end])));
+        rest = value_part.get(value_end + 1..).unwrap_or("").trim_start();
+    }
+    attributes
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(doc: &DocComment) -> Option<String> {
+        doc.summary.as_deref().map(plain_text)
+    }
+
+    fn parse(lines: &[&str]) -> DocComment {
+        let lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        parse_doc_comment(&lines).unwrap()
+    }
+
+    #[test]
+    fn test_parse_single_line_summary() {
+        let doc = parse(&["/// <summary>This is a single-line summary</summary>"]);
+        assert_eq!(
+            summary(&doc),
+            Some("This is a single-line summary".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_multi_line_summary() {
+        let doc = parse(&[
+            "/// <summary>",
+            "/// This is a multi-line",
+            "/// summary",
+            "/// </summary>",
+        ]);
+        assert_eq!(
+            summary(&doc),
+            Some("This is a multi-line summary".to_string())
+        );
+
+        let doc = parse(&[
+            "/// <summary>This is a single-line summary inside multi-line",
+            "/// but not ended yet",
+            "/// </summary>",
+        ]);
+        assert_eq!(
+            summary(&doc),
+            Some("This is a single-line summary inside multi-line but not ended yet".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_all_sections() {
+        let doc = parse(&[
+            "/// <summary>Reads a <see cref=\"Stream\"/> &amp; more.</summary>",
+            "/// <remarks>Some remarks.</remarks>",
+            "/// <example><code>var x = Read();</code></example>",
+            "/// <param name=\"path\">The path.</param>",
+            "/// <param name='count'>How many.</param>",
+            "/// <typeparam name=\"T\">The item type.</typeparam>",
+            "/// <returns>The items.</returns>",
+            "/// <exception cref=\"IOException\">When reading fails.</exception>",
+        ]);
+        assert_eq!(summary(&doc), Some("Reads a & more.".to_string()));
+        assert_eq!(plain_text(doc.remarks.as_ref().unwrap()), "Some remarks.");
+        assert_eq!(plain_text(doc.example.as_ref().unwrap()), "var x = Read();");
+        assert_eq!(plain_text(doc.returns.as_ref().unwrap()), "The items.");
+        assert_eq!(doc.params.len(), 2);
+        assert_eq!(plain_text(&doc.param("count").unwrap().text), "How many.");
+        assert_eq!(doc.type_params[0].name, "T");
+        assert_eq!(doc.exceptions[0].name, "IOException");
+
+        let DocNode::Element { name, .. } = &doc.summary.as_ref().unwrap()[1] else {
+            panic!("expected an element");
+        };
+        assert_eq!(name, "see");
+        assert_eq!(
+            doc.summary.as_ref().unwrap()[1].attribute("cref"),
+            Some("Stream")
+        );
+    }
+
+    #[test]
+    fn test_parse_lenient_markup() {
+        let doc = parse(&["/// <summary>Checks a < b <b>bold", "/// </summary>"]);
+        assert_eq!(summary(&doc), Some("Checks a < b bold".to_string()));
+
+        let doc = parse(&["/// <summary>Stray </b> closing tag.</summary>"]);
+        assert_eq!(summary(&doc), Some("Stray closing tag.".to_string()));
+
+        let doc = parse(&["/// Plain text without tags."]);
+        assert_eq!(summary(&doc), Some("Plain text without tags.".to_string()));
+
+        let doc = parse(&["/**\n * <summary>Block comment.</summary>\n */"]);
+        assert_eq!(summary(&doc), Some("Block comment.".to_string()));
+
+        let doc = parse(&["/** <summary>Unterminated block.</summary>"]);
+        assert_eq!(summary(&doc), Some("Unterminated block.".to_string()));
+        let doc = parse(&["/** Unterminated plain text."]);
+        assert_eq!(summary(&doc), Some("Unterminated plain text.".to_string()));
+
+        let lines = vec!["/// <remarks></remarks>".to_string()];
+        assert!(parse_doc_comment(&lines).is_some());
+        assert!(parse_doc_comment(&["///".to_string()]).is_none());
+    }
+
+    #[test]
+    fn test_parse_inheritdoc() {
+        let doc = parse(&["/// <inheritdoc/>"]);
+        assert_eq!(doc.inheritdoc, Some(InheritDoc { cref: None }));
+
+        let doc = parse(&[
+            "/// <inheritdoc cref=\"IRepository.Save\"/>",
+            "/// <remarks>Own remarks.</remarks>",
+        ]);
+        assert_eq!(
+            doc.inheritdoc,
+            Some(InheritDoc {
+                cref: Some("IRepository.Save".to_string())
+            })
+        );
+
+        let inherited = parse(&[
+            "/// <summary>Saves.</summary>",
+            "/// <remarks>Base remarks.</remarks>",
+            "/// <param name=\"a\">A.</param>",
+        ]);
+        let combined = doc.inherit_from(&inherited);
+        assert_eq!(summary(&combined), Some("Saves.".to_string()));
+        assert_eq!(
+            plain_text(combined.remarks.as_ref().unwrap()),
+            "Own remarks."
+        );
+        assert_eq!(combined.params.len(), 1);
+        assert_eq!(combined.inheritdoc, None);
+    }
+}
@@ -1,23 +1,279 @@
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsStr;
 use std::fs::File;
 use std::io;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use strum::IntoEnumIterator;
 
-use crate::parser::{ConstructInfo, ConstructType};
+use crate::config::{span_path, Config, VAR_ENV_PREFIX};
+use crate::doc_comment::{strip_cref, strip_generic_arity, DocComment, DocEntry, DocNode, DocText};
+use crate::markdown::{anchor, render_markdown};
+use crate::parser::{
+    AccessModifier, Attribute, ConstructInfo, ConstructType, EnumMember, MemberInfo, MemberType,
+    Modifiers, Parameter, SourceSpan, TypeConstraint,
+};
+use crate::template::{Filter, Filters, Position, Schema, Template, TemplateError, Value};
 
-pub fn load_template(template_file: &PathBuf) -> Result<String, io::Error> {
-    let mut template_content = String::new();
-    let mut file = File::open(template_file)?;
-    file.read_to_string(&mut template_content)?;
-    Ok(template_content)
+// Loads a template along with the partials it includes and the base templates it extends, which
+// are resolved relative to it.
+pub fn load_template(template_file: &Path) -> Result<Template, io::Error> {
+    load_extending(template_file, &mut Vec::new())
 }
 
+// `chain` holds the canonical paths and names of the templates extending this one.
+fn load_extending(
+    template_file: &Path,
+    chain: &mut Vec<(PathBuf, String)>,
+) -> Result<Template, io::Error> {
+    let name = template_file.display().to_string();
+    let invalid = |e: TemplateError| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            e.in_file(Some(&name)).to_string(),
+        )
+    };
+    let template_content = read_file(template_file)?;
+    let directory = template_file.parent().unwrap_or(Path::new(""));
+    let extension = template_file.extension();
+    let mut template = Template::parse(&template_content)
+        .map_err(invalid)?
+        .with_file(&name);
+    template
+        .load_partials(&|partial| {
+            let path = partial_path(directory, partial, extension);
+            read_file(&path)
+                .map(|content| (path.display().to_string(), content))
+                .map_err(|e| e.to_string())
+        })
+        .map_err(invalid)?;
+    let Some((base, position)) = template.extends() else {
+        return Ok(template);
+    };
+
+    let base_file = directory.join(base);
+    chain.push((template_file.canonicalize()?, name.clone()));
+    if let Some(start) = base_file
+        .canonicalize()
+        .ok()
+        .and_then(|base| chain.iter().position(|(path, _)| *path == base))
+    {
+        let mut cycle: Vec<&str> = chain[start..].iter().map(|(_, n)| n.as_str()).collect();
+        cycle.push(&chain[start].1);
+        let message = format!("templates extend each other: {}", cycle.join(" > "));
+        return Err(invalid(TemplateError {
+            message,
+            file: None,
+            position,
+        }));
+    }
+    let base_template = match load_extending(&base_file, chain) {
+        Err(e) if e.kind() != io::ErrorKind::InvalidData => {
+            return Err(invalid(TemplateError {
+                message: format!("cannot load base template `{}`: {}", base, e),
+                file: None,
+                position,
+            }))
+        }
+        result => result?,
+    };
+    chain.pop();
+    template.extend(base_template).map_err(invalid)
+}
+
+fn read_file(path: &Path) -> Result<String, io::Error> {
+    let mut content = String::new();
+    let mut file = File::open(path)?;
+    file.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+// `{{> header}}` refers to `header.md` next to a `.md` template, unless there is a file named
+// `header` without an extension.
+fn partial_path(directory: &Path, name: &str, extension: Option<&OsStr>) -> PathBuf {
+    let path = directory.join(name);
+    match extension {
+        Some(extension) if path.extension().is_none() && !path.is_file() => {
+            path.with_extension(extension)
+        }
+        _ => path,
+    }
+}
+
+// Renders the `{{ }}` blocks of the template first, then expands the single-line
+// `[class_name]` style placeholders of the result.
 fn expand_template(
+    template: &Template,
+    construct_map: &HashMap<ConstructType, Vec<ConstructInfo>>,
+    config: &Config,
+) -> Result<String, TemplateError> {
+    let links = LinkTargets::new(construct_map);
+    let context = template_context(construct_map, &links, config);
+    // Older templates use single-line `[system_name]` style placeholders for variables
+    let builtin = builtin_placeholders();
+    let mut variables = template.required_variables("vars.");
+    for placeholder in template.placeholders() {
+        if !builtin.contains(&placeholder.0)
+            && variables.iter().all(|(n, _, _)| *n != placeholder.0)
+        {
+            variables.push(placeholder);
+        }
+    }
+    let missing: Vec<(String, Option<String>, Position)> = variables
+        .into_iter()
+        .filter(|(name, _, _)| !config.vars.contains_key(name))
+        .collect();
+    if let Some((_, file, position)) = missing.first() {
+        let names: Vec<String> = missing
+            .iter()
+            .map(|(name, _, _)| format!("`{}`", name))
+            .collect();
+        let (noun, verb) = match names.len() {
+            1 => ("variable", "is"),
+            _ => ("variables", "are"),
+        };
+        return Err(TemplateError {
+            message: format!(
+                "template {} {} {} not set, pass with `--var <name>=<value>`, in the `[vars]` of the config file or as `{}<NAME>` environment variables",
+                noun,
+                names.join(", "),
+                verb,
+                VAR_ENV_PREFIX
+            ),
+            file: file.clone(),
+            position: *position,
+        });
+    }
+    let mut template = template.clone();
+    template.fill_placeholders(&config.vars);
+    let rendered = template.render(&context, &template_filters())?;
+    Ok(expand_lines(&rendered, construct_map, &links, config))
+}
+
+// A construct with one item in every list, so that `construct_value` gives the fields of the items
+// of every list a template can loop over, e.g. the `params` of a member of a struct.
+fn schema_construct(construct_type: ConstructType) -> ConstructInfo {
+    let entry = || DocEntry {
+        name: String::new(),
+        text: Vec::new(),
+    };
+    let doc = || DocComment {
+        params: vec![entry()],
+        type_params: vec![entry()],
+        exceptions: vec![entry()],
+        ..DocComment::default()
+    };
+    let attribute = || Attribute {
+        name: String::new(),
+        arguments: vec![String::new()],
+        target: None,
+    };
+    let parameter = || Parameter {
+        name: String::new(),
+        type_name: String::new(),
+        modifier: None,
+        default_value: None,
+    };
+    let mut construct = ConstructInfo::new(construct_type, "");
+    construct.doc = Some(doc());
+    construct.members = vec![MemberInfo {
+        doc: Some(doc()),
+        access_modifier: AccessModifier::Public,
+        member_type: MemberType::Method,
+        name: String::new(),
+        type_name: None,
+        parameters: vec![parameter()],
+        modifiers: Modifiers::default(),
+        attributes: vec![attribute()],
+        region: None,
+        accessors: vec![String::new()],
+        signature: String::new(),
+    }];
+    construct.parameters = vec![parameter()];
+    construct.type_parameters = vec![String::new()];
+    construct.base_types = vec![String::new()];
+    construct.interfaces = vec![String::new()];
+    construct.enum_members = vec![EnumMember {
+        doc: Some(doc()),
+        attributes: vec![attribute()],
+        name: String::new(),
+        value: None,
+    }];
+    construct.attributes = vec![attribute()];
+    construct
+}
+
+// The single-line placeholders besides `[<construct>_name]` and the template variables.
+const PLACEHOLDERS: [&str; 29] = [
+    "summary",
+    "one_sentence_summary",
+    "remarks",
+    "example",
+    "returns",
+    "value",
+    "params",
+    "type_params",
+    "exceptions",
+    "access_modifier",
+    "namespace",
+    "full_name",
+    "qualified_name",
+    "parent_name",
+    "indent",
+    "type_parameters",
+    "base_types",
+    "base_class",
+    "interfaces",
+    "constraints",
+    "modifiers",
+    "underlying_type",
+    "attributes",
+    "region",
+    "obsolete_message",
+    "file",
+    "line",
+    "source_link",
+    "enum_values",
+];
+
+fn builtin_placeholders() -> Vec<String> {
+    let mut placeholders: Vec<String> = PLACEHOLDERS.iter().map(|p| p.to_string()).collect();
+    placeholders.extend(ConstructType::iter().map(|t| format!("{}_name", t.as_lowercase())));
+    placeholders
+}
+
+// Reports the variables, filters and placeholders of the template that are unknown, so that a
+// misspelled `[clas_name]` doesn't pass through into the documentation silently.
+pub fn check_template(template: &Template, config: &Config) -> Vec<TemplateError> {
+    let construct_map =
+        categorize_constructs(ConstructType::iter().map(schema_construct).collect());
+    let links = LinkTargets::new(&construct_map);
+    let context = template_context(&construct_map, &links, config);
+    let mut placeholders = builtin_placeholders();
+    placeholders.extend(config.vars.keys().cloned());
+    let mut conditions: Vec<String> = Modifiers::default()
+        .flags()
+        .into_iter()
+        .map(|(keyword, _)| keyword.to_string())
+        .collect();
+    conditions.extend(["flags".to_string(), "obsolete".to_string()]);
+    template.check(&Schema {
+        context: &context,
+        filters: &template_filters(),
+        user_prefix: "vars.",
+        placeholders: &placeholders,
+        conditions: &conditions,
+    })
+}
+
+fn expand_lines(
     template: &str,
     construct_map: &HashMap<ConstructType, Vec<ConstructInfo>>,
+    links: &LinkTargets,
+    config: &Config,
 ) -> String {
     let mut expanded_template = String::new();
 
@@ -29,25 +285,71 @@ fn expand_template(
                 if let Some(constructs) = construct_map.get(&construct_type) {
                     pass_through_line = false;
                     for item in constructs {
+                        let Some(line) = apply_conditions(line, item) else {
+                            continue;
+                        };
                         let mut expanded_line = line.replace(&construct_placeholder, &item.name);
+                        let summary = item
+                            .doc
+                            .as_ref()
+                            .and_then(|doc| doc.summary.as_ref())
+                            .map(|summary| links.render(summary));
                         expanded_line = expanded_line.replace(
                             "[summary]",
-                            &item
-                                .docstring
-                                .clone()
-                                .unwrap_or_else(|| "[summary]".to_string()),
+                            &summary.clone().unwrap_or_else(|| "[summary]".to_string()),
                         );
                         expanded_line = expanded_line.replace(
                             "[one_sentence_summary]",
-                            substring_until_dot(
-                                &item
-                                    .docstring
-                                    .clone()
-                                    .unwrap_or_else(|| "[one_sentence_summary]".to_string()),
+                            &summary.map_or_else(
+                                || "[one_sentence_summary]".to_string(),
+                                |summary| first_sentence(&summary),
                             ),
                         );
+                        if let Some(doc) = &item.doc {
+                            expanded_line = expand_doc_sections(&expanded_line, doc, links);
+                        }
                         expanded_line = expanded_line
                             .replace("[access_modifier]", &item.access_modifier.to_string());
+                        expanded_line = expanded_line
+                            .replace("[namespace]", item.namespace.as_deref().unwrap_or_default())
+                            .replace("[full_name]", &item.full_name())
+                            .replace("[qualified_name]", &item.qualified_name())
+                            .replace("[parent_name]", item.parent.as_deref().unwrap_or_default())
+                            .replace("[indent]", &"  ".repeat(item.depth()))
+                            .replace("[type_parameters]", &format_type_parameters(item))
+                            .replace("[base_types]", &item.base_types.join(", "))
+                            .replace(
+                                "[base_class]",
+                                item.base_class.as_deref().unwrap_or_default(),
+                            )
+                            .replace("[interfaces]", &item.interfaces.join(", "))
+                            .replace("[constraints]", &format_constraints(&item.constraints))
+                            .replace("[modifiers]", &item.modifiers.keywords().join(" "))
+                            .replace(
+                                "[underlying_type]",
+                                item.underlying_type.as_deref().unwrap_or("int"),
+                            )
+                            .replace("[attributes]", &format_attributes(&item.attributes))
+                            .replace("[region]", item.region.as_deref().unwrap_or_default())
+                            .replace(
+                                "[obsolete_message]",
+                                &item
+                                    .obsolete()
+                                    .and_then(|obsolete| obsolete.message())
+                                    .unwrap_or_default(),
+                            );
+                        if let Some(span) = &item.span {
+                            expanded_line = expanded_line
+                                .replace("[file]", &span_path(span))
+                                .replace("[line]", &span.start_line.to_string());
+                        }
+                        let link = item.span.as_ref().and_then(|span| config.source_link(span));
+                        expanded_line = expanded_line
+                            .replace("[source_link]", link.as_deref().unwrap_or_default());
+                        if !item.enum_members.is_empty() {
+                            expanded_line = expanded_line
+                                .replace("[enum_values]", &format_enum_values(item, links));
+                        }
                         expanded_template.push_str(&expanded_line);
                         expanded_template.push('\n');
                     }
@@ -63,8 +365,699 @@ fn expand_template(
     expanded_template
 }
 
-fn substring_until_dot(s: &str) -> &str {
-    s.split('.').next().unwrap_or(s)
+// Evaluates `[if static]` and `[if !static]` style conditions on a modifier, `flags` or `obsolete`. Returns the line
+// without its conditions if they all hold, `None` if the line should be left out for this
+// construct. Conditions on unknown words are dropped, `check_template` reports them.
+fn apply_conditions(line: &str, construct: &ConstructInfo) -> Option<String> {
+    let mut result = String::new();
+    let mut rest = line;
+    while let Some(start) = rest.find("[if ") {
+        let Some(end) = rest[start..].find(']').map(|end| start + end) else {
+            break;
+        };
+        let condition = rest[start + 4..end].trim();
+        let (negated, keyword) = match condition.strip_prefix('!') {
+            Some(keyword) => (true, keyword.trim()),
+            None => (false, condition),
+        };
+        if construct.condition(keyword) == Some(negated) {
+            return None;
+        }
+        result.push_str(&rest[..start]);
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Some(result)
+}
+
+// Replaces the placeholders of the documentation comment sections that are present. Missing
+// sections keep their placeholder, just like a missing summary.
+fn expand_doc_sections(line: &str, doc: &DocComment, links: &LinkTargets) -> String {
+    let mut expanded_line = line.to_string();
+    let sections = [
+        ("[remarks]", &doc.remarks),
+        ("[example]", &doc.example),
+        ("[returns]", &doc.returns),
+        ("[value]", &doc.value),
+    ];
+    for (placeholder, section) in sections {
+        if let Some(section) = section {
+            expanded_line = expanded_line.replace(placeholder, &links.render(section));
+        }
+    }
+    let entry_lists = [
+        ("[params]", &doc.params),
+        ("[type_params]", &doc.type_params),
+        ("[exceptions]", &doc.exceptions),
+    ];
+    for (placeholder, entries) in entry_lists {
+        if !entries.is_empty() {
+            expanded_line = expanded_line.replace(placeholder, &format_doc_entries(entries, links));
+        }
+    }
+    expanded_line
+}
+
+fn format_doc_entries(entries: &[DocEntry], links: &LinkTargets) -> String {
+    entries
+        .iter()
+        .map(|entry| format!("`{}`: {}", entry.name, links.render(&entry.text)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// `<T, U>`, so that `[class_name][type_parameters]` reads like the declaration.
+fn format_type_parameters(construct: &ConstructInfo) -> String {
+    if construct.type_parameters.is_empty() {
+        return String::new();
+    }
+    format!("<{}>", construct.type_parameters.join(", "))
+}
+
+// A Markdown table of the members of an enum. It starts with a blank line so that it can follow
+// the entry of the enum on the same template line.
+fn format_enum_values(construct: &ConstructInfo, links: &LinkTargets) -> String {
+    let mut table = String::from("\n\n| Name | Value | Description |\n| --- | --- | --- |");
+    for (member, value) in construct.enum_members.iter().zip(enum_values(construct)) {
+        let description = member
+            .doc
+            .as_ref()
+            .and_then(|doc| doc.summary.as_ref())
+            .map(|summary| links.render(summary).replace('\n', " "))
+            .unwrap_or_default();
+        table.push_str(&format!(
+            "\n| `{}` | `{}` | {} |",
+            member.name,
+            // Pipes would end the table cell, even inside code spans
+            value.replace('|', "\\|"),
+            description.replace('|', "\\|")
+        ));
+    }
+    table
+}
+
+// The values of the members of an enum. Members without a value continue counting from the
+// previous one.
+fn enum_values(construct: &ConstructInfo) -> Vec<String> {
+    let mut values: Vec<String> = Vec::new();
+    for member in &construct.enum_members {
+        let value = match (&member.value, values.last()) {
+            (Some(value), _) => value.clone(),
+            (None, None) => "0".to_string(),
+            (None, Some(previous)) => match parse_integer(previous) {
+                Some(number) => (number + 1).to_string(),
+                None if previous.contains(' ') => format!("({}) + 1", previous),
+                None => format!("{} + 1", previous),
+            },
+        };
+        values.push(value);
+    }
+    values
+}
+
+// Parses C# integer literals such as `42`, `0x1F`, `0b_1010` or `1UL`.
+fn parse_integer(literal: &str) -> Option<i128> {
+    let literal = literal
+        .trim()
+        .trim_end_matches(['u', 'U', 'l', 'L'])
+        .replace('_', "");
+    let (digits, radix) = match literal.get(..2) {
+        Some("0x" | "0X") => (&literal[2..], 16),
+        Some("0b" | "0B") => (&literal[2..], 2),
+        _ => (literal.as_str(), 10),
+    };
+    i128::from_str_radix(digits, radix).ok()
+}
+
+fn format_attributes(attributes: &[Attribute]) -> String {
+    attributes
+        .iter()
+        .map(|attribute| attribute.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn format_constraints(constraints: &[TypeConstraint]) -> String {
+    constraints
+        .iter()
+        .map(|c| format!("where {} : {}", c.type_parameter, c.constraints.join(", ")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// The data of `{{ }}` templates: a list per construct type such as `classes` or
+// `record_structs`, `constructs` with all of them and the user supplied `vars`.
+fn template_context(
+    construct_map: &HashMap<ConstructType, Vec<ConstructInfo>>,
+    links: &LinkTargets,
+    config: &Config,
+) -> Value {
+    let mut context = BTreeMap::new();
+    let mut all = Vec::new();
+    for construct_type in ConstructType::iter() {
+        let items: Vec<Value> = construct_map
+            .get(&construct_type)
+            .into_iter()
+            .flatten()
+            .map(|construct| construct_value(construct, links, config))
+            .collect();
+        all.extend(items.iter().cloned());
+        context.insert(construct_type.as_plural(), Value::List(items));
+    }
+    context.insert("constructs".to_string(), Value::List(all));
+    context.insert(
+        "vars".to_string(),
+        Value::object(
+            config
+                .vars
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.clone().into())),
+        ),
+    );
+    Value::Object(context)
+}
+
+// The fields of a construct are named like the single-line placeholders, e.g. `{{full_name}}`
+// for `[full_name]`, with `members`, `params` and `enum_values` to loop over.
+fn construct_value(construct: &ConstructInfo, links: &LinkTargets, config: &Config) -> Value {
+    let mut value = doc_values(construct.doc.as_ref(), links);
+    value.extend(modifier_values(&construct.modifiers));
+    let span = construct.span.as_ref();
+    value.extend(
+        [
+            ("name", construct.name.clone().into()),
+            ("kind", construct.construct_type.to_string().into()),
+            ("anchor", anchor(&construct.full_name()).into()),
+            (
+                "access_modifier",
+                construct.access_modifier.to_string().into(),
+            ),
+            ("namespace", construct.namespace.clone().into()),
+            ("full_name", construct.full_name().into()),
+            ("qualified_name", construct.qualified_name().into()),
+            ("parent_name", construct.parent.clone().into()),
+            ("type_parameters", format_type_parameters(construct).into()),
+            ("base_types", construct.base_types.clone().into()),
+            ("base_class", construct.base_class.clone().into()),
+            ("interfaces", construct.interfaces.clone().into()),
+            (
+                "constraints",
+                format_constraints(&construct.constraints).into(),
+            ),
+            ("modifiers", construct.modifiers.keywords().join(" ").into()),
+            (
+                "underlying_type",
+                construct.underlying_type.as_deref().unwrap_or("int").into(),
+            ),
+            ("attributes", attribute_values(&construct.attributes)),
+            ("region", construct.region.clone().into()),
+            ("is_flags", construct.is_flags().into()),
+            ("is_obsolete", construct.obsolete().is_some().into()),
+            (
+                "obsolete_message",
+                construct.obsolete().and_then(|o| o.message()).into(),
+            ),
+            ("file", span.map(span_path).into()),
+            ("folder", span.map(span_folder).into()),
+            ("line", span.map(|span| span.start_line.to_string()).into()),
+            (
+                "source_link",
+                span.and_then(|span| config.source_link(span)).into(),
+            ),
+            (
+                "params",
+                parameter_values(&construct.parameters, construct.doc.as_ref(), links),
+            ),
+            (
+                "members",
+                Value::List(
+                    construct
+                        .members
+                        .iter()
+                        .map(|member| member_value(member, links))
+                        .collect(),
+                ),
+            ),
+            (
+                "enum_values",
+                Value::List(
+                    construct
+                        .enum_members
+                        .iter()
+                        .zip(enum_values(construct))
+                        .map(|(member, value)| {
+                            Value::object([
+                                ("name", member.name.clone().into()),
+                                ("value", value.into()),
+                                (
+                                    "summary",
+                                    doc_text(member.doc.as_ref(), links, |d| &d.summary),
+                                ),
+                                ("attributes", attribute_values(&member.attributes)),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+        ]
+        .map(|(key, field)| (key.to_string(), field)),
+    );
+    Value::Object(value)
+}
+
+// The directory of the file, e.g. `src/Logging` for `src/Logging/Logger.cs`.
+fn span_folder(span: &SourceSpan) -> String {
+    let path = span_path(span);
+    path.rsplit_once('/')
+        .map_or(String::new(), |(folder, _)| folder.to_string())
+}
+
+fn member_value(member: &MemberInfo, links: &LinkTargets) -> Value {
+    let mut value = doc_values(member.doc.as_ref(), links);
+    value.extend(modifier_values(&member.modifiers));
+    let obsolete = member.attributes.iter().find(|a| a.is("Obsolete"));
+    value.extend(
+        [
+            ("name", member.name.clone().into()),
+            ("kind", member.member_type.to_string().into()),
+            ("type", member.type_name.clone().into()),
+            ("signature", member.signature.clone().into()),
+            ("access_modifier", member.access_modifier.to_string().into()),
+            ("modifiers", member.modifiers.keywords().join(" ").into()),
+            ("accessors", member.accessors.clone().into()),
+            ("attributes", attribute_values(&member.attributes)),
+            ("region", member.region.clone().into()),
+            ("is_obsolete", obsolete.is_some().into()),
+            (
+                "obsolete_message",
+                obsolete.and_then(|o| o.message()).into(),
+            ),
+            (
+                "params",
+                parameter_values(&member.parameters, member.doc.as_ref(), links),
+            ),
+        ]
+        .map(|(key, field)| (key.to_string(), field)),
+    );
+    Value::Object(value)
+}
+
+// Every documentation section is present, empty when it is missing, so that a member without
+// `<returns>` does not show the `{{returns}}` of its type.
+fn doc_values(doc: Option<&DocComment>, links: &LinkTargets) -> BTreeMap<String, Value> {
+    let entries = |entries: fn(&DocComment) -> &Vec<DocEntry>| {
+        Value::List(
+            doc.map(entries)
+                .into_iter()
+                .flatten()
+                .map(|entry| {
+                    Value::object([
+                        ("name", entry.name.clone().into()),
+                        ("summary", links.render(&entry.text).into()),
+                    ])
+                })
+                .collect(),
+        )
+    };
+    [
+        ("summary", doc_text(doc, links, |d| &d.summary)),
+        ("remarks", doc_text(doc, links, |d| &d.remarks)),
+        ("example", doc_text(doc, links, |d| &d.example)),
+        ("returns", doc_text(doc, links, |d| &d.returns)),
+        ("value", doc_text(doc, links, |d| &d.value)),
+        ("type_params", entries(|d| &d.type_params)),
+        ("exceptions", entries(|d| &d.exceptions)),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value))
+    .collect()
+}
+
+fn doc_text(
+    doc: Option<&DocComment>,
+    links: &LinkTargets,
+    section: fn(&DocComment) -> &Option<DocText>,
+) -> Value {
+    doc.and_then(|doc| section(doc).as_ref())
+        .map(|text| links.render(text))
+        .into()
+}
+
+// `is_static`, `is_abstract` and so on for every modifier keyword.
+fn modifier_values(modifiers: &Modifiers) -> Vec<(String, Value)> {
+    modifiers
+        .flags()
+        .into_iter()
+        .map(|(keyword, is_set)| (format!("is_{}", keyword), is_set.into()))
+        .collect()
+}
+
+// The declared parameters with their `<param>` documentation. Without declared parameters, e.g.
+// for a class, the documented ones are used.
+fn parameter_values(
+    parameters: &[Parameter],
+    doc: Option<&DocComment>,
+    links: &LinkTargets,
+) -> Value {
+    let summary = |name: &str| -> Value {
+        doc.and_then(|doc| doc.param(name))
+            .map(|entry| links.render(&entry.text))
+            .into()
+    };
+    if parameters.is_empty() {
+        return Value::List(
+            doc.map(|doc| doc.params.as_slice())
+                .unwrap_or_default()
+                .iter()
+                .map(|entry| {
+                    Value::object([
+                        ("name", entry.name.clone().into()),
+                        ("summary", summary(&entry.name)),
+                    ])
+                })
+                .collect(),
+        );
+    }
+    Value::List(
+        parameters
+            .iter()
+            .map(|parameter| {
+                Value::object([
+                    ("name", parameter.name.clone().into()),
+                    ("type", parameter.type_name.clone().into()),
+                    ("modifier", parameter.modifier.clone().into()),
+                    ("default_value", parameter.default_value.clone().into()),
+                    ("summary", summary(&parameter.name)),
+                ])
+            })
+            .collect(),
+    )
+}
+
+fn attribute_values(attributes: &[Attribute]) -> Value {
+    Value::List(
+        attributes
+            .iter()
+            .map(|attribute| {
+                Value::object([
+                    ("name", attribute.name.clone().into()),
+                    ("arguments", attribute.arguments.clone().into()),
+                    ("target", attribute.target.clone().into()),
+                    ("text", attribute.to_string().into()),
+                ])
+            })
+            .collect(),
+    )
+}
+
+// Anchors of the documented constructs, used to turn `<see cref="..."/>` into links. Anchors are
+// made from the full name, so that types with the same name in different namespaces don't share
+// one. Constructs can be referred to by their full name, or by their qualified or simple name when
+// only one construct has it.
+struct LinkTargets {
+    anchors: HashMap<String, String>,
+}
+
+impl LinkTargets {
+    fn new(construct_map: &HashMap<ConstructType, Vec<ConstructInfo>>) -> Self {
+        let mut anchors = HashMap::new();
+        let mut short_names: HashMap<String, Vec<String>> = HashMap::new();
+        for construct in construct_map.values().flatten() {
+            let full_name = construct.full_name();
+            let target = anchor(&full_name);
+            for name in [construct.qualified_name(), construct.name.clone()] {
+                if name != full_name {
+                    short_names.entry(name).or_default().push(target.clone());
+                }
+            }
+            anchors.insert(full_name, target);
+        }
+        for (name, mut targets) in short_names {
+            targets.dedup();
+            if let [target] = targets.as_slice() {
+                anchors.entry(name).or_insert_with(|| target.clone());
+            }
+        }
+        Self { anchors }
+    }
+
+    // Member references such as `App.Logger.Log` link to the entry of their type.
+    fn resolve(&self, cref: &str) -> Option<String> {
+        let mut name = strip_generic_arity(strip_cref(cref));
+        loop {
+            if let Some(target) = self.anchors.get(&name) {
+                return Some(target.clone());
+            }
+            name.truncate(name.rfind('.')?);
+        }
+    }
+
+    fn render(&self, nodes: &[DocNode]) -> String {
+        render_markdown(nodes, &|cref| self.resolve(cref))
+    }
+}
+
+// The filters usable in `{{value | filter}}` template variables. Filters on text apply to each
+// item of a list.
+fn template_filters() -> Filters {
+    let filters: [(&'static str, Filter); 7] = [
+        ("first_sentence", |value, arguments| {
+            no_arguments(arguments)?;
+            Ok(map_text(value, &first_sentence))
+        }),
+        ("kebab_case", |value, arguments| {
+            no_arguments(arguments)?;
+            Ok(map_text(value, &kebab_case))
+        }),
+        ("escape_html", |value, arguments| {
+            no_arguments(arguments)?;
+            Ok(map_text(value, &escape_html))
+        }),
+        ("truncate", |value, arguments| {
+            let [length] = arguments else {
+                return Err("expected a length, e.g. `truncate(80)`".to_string());
+            };
+            let length: usize = length
+                .parse()
+                .map_err(|_| format!("`{}` is not a length", length))?;
+            Ok(map_text(value, &|text| truncate(text, length)))
+        }),
+        ("join", |value, arguments| {
+            let separator = match arguments {
+                [] => ", ",
+                [separator] => separator.as_str(),
+                _ => return Err("expected a separator, e.g. `join(\", \")`".to_string()),
+            };
+            Ok(match value {
+                Value::List(items) => items
+                    .iter()
+                    .map(Value::render)
+                    .collect::<Vec<_>>()
+                    .join(separator)
+                    .into(),
+                value => value.render().into(),
+            })
+        }),
+        ("sort", |value, arguments| {
+            if arguments.is_empty() {
+                return Err(
+                    "expected the fields to sort by, e.g. `sort(namespace, name)`".to_string(),
+                );
+            }
+            let Value::List(items) = value else {
+                return Ok(value.clone());
+            };
+            for field in arguments {
+                check_field(items, field)?;
+            }
+            let mut items = items.clone();
+            items.sort_by(|a, b| {
+                arguments
+                    .iter()
+                    .map(|field| compare_field(a, b, field))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            });
+            Ok(Value::List(items))
+        }),
+        ("group_by", |value, arguments| {
+            let [field] = arguments else {
+                return Err(
+                    "expected the field to group by, e.g. `group_by(namespace)`".to_string()
+                );
+            };
+            let Value::List(items) = value else {
+                return Ok(value.clone());
+            };
+            check_field(items, field)?;
+            let mut groups: Vec<(String, Vec<Value>)> = Vec::new();
+            for item in items {
+                let key = item.get(field).map(Value::render).unwrap_or_default();
+                match groups.iter_mut().find(|(name, _)| *name == key) {
+                    Some((_, group)) => group.push(item.clone()),
+                    None => groups.push((key, vec![item.clone()])),
+                }
+            }
+            Ok(Value::List(
+                groups
+                    .into_iter()
+                    .map(|(name, items)| {
+                        Value::object([("name", name.into()), ("items", Value::List(items))])
+                    })
+                    .collect(),
+            ))
+        }),
+    ];
+    filters.into_iter().collect()
+}
+
+// A misspelled field would leave the list as it is, so it is reported instead. `source` stands for
+// the file and line.
+fn check_field(items: &[Value], field: &str) -> Result<(), String> {
+    let missing = |item: &Value| matches!(item, Value::Object(_)) && item.get(field).is_none();
+    if field != "source" && items.iter().any(missing) {
+        return Err(format!("the items have no field `{}`", field));
+    }
+    Ok(())
+}
+
+// Access modifiers sort from `public` to `private`, numbers by value, `source` by file and line
+// and other fields alphabetically.
+fn compare_field(a: &Value, b: &Value, field: &str) -> Ordering {
+    if field == "source" {
+        return compare_field(a, b, "file").then_with(|| compare_field(a, b, "line"));
+    }
+    let text = |value: &Value| value.get(field).map(Value::render).unwrap_or_default();
+    let (a, b) = (text(a), text(b));
+    if field == "access_modifier" {
+        let rank = |text: &str| AccessModifier::from_str(text).map_or(u8::MAX, |m| m.rank());
+        return rank(&a).cmp(&rank(&b));
+    }
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        _ => a
+            .to_lowercase()
+            .cmp(&b.to_lowercase())
+            .then_with(|| a.cmp(&b)),
+    }
+}
+
+fn no_arguments(arguments: &[String]) -> Result<(), String> {
+    match arguments {
+        [] => Ok(()),
+        _ => Err("takes no arguments".to_string()),
+    }
+}
+
+fn map_text(value: &Value, f: &dyn Fn(&str) -> String) -> Value {
+    match value {
+        Value::List(items) => Value::List(items.iter().map(|item| map_text(item, f)).collect()),
+        value => f(&value.render()).into(),
+    }
+}
+
+// Words that end in a period without ending the sentence.
+const ABBREVIATIONS: [&str; 10] = [
+    "e.g", "i.e", "vs", "cf", "approx", "incl", "no", "fig", "ca", "resp",
+];
+
+// The text up to and including the punctuation that ends its first sentence, or up to the first
+// paragraph break. Periods inside identifiers and numbers such as `System.IO` or `1.2`, after
+// abbreviations such as `e.g.`, and inside code spans or links, i.e. `<c>` and `<see>`, don't end
+// a sentence.
+fn first_sentence(text: &str) -> String {
+    let text = text.trim();
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut in_code = false;
+    let mut in_link_target = false;
+    let mut link_depth = 0;
+    for (i, &(offset, c)) in chars.iter().enumerate() {
+        let next = chars.get(i + 1).map(|&(_, next)| next);
+        match c {
+            '`' => in_code = !in_code,
+            _ if in_code => {}
+            '[' => link_depth += 1,
+            ']' if link_depth > 0 => {
+                link_depth -= 1;
+                in_link_target = next == Some('(');
+            }
+            ')' if in_link_target => in_link_target = false,
+            _ if in_link_target || link_depth > 0 => {}
+            '\n' if next == Some('\n') => return text[..offset].trim_end().to_string(),
+            '.' | '!' | '?' => {
+                // Closing quotes and parentheses belong to the sentence
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&(_, c)| !matches!(c, '"' | '\'' | ')' | '.' | '!' | '?'))
+                    .map_or(chars.len(), |p| i + 1 + p);
+                let followed_by_space = chars.get(end).is_none_or(|&(_, c)| c.is_whitespace());
+                let next_word = chars[end..]
+                    .iter()
+                    .map(|&(_, c)| c)
+                    .find(|c| !c.is_whitespace());
+                if followed_by_space
+                    && !next_word.is_some_and(char::is_lowercase)
+                    && !(c == '.' && is_abbreviation(&text[..offset]))
+                {
+                    let end_offset = chars.get(end).map_or(text.len(), |&(offset, _)| offset);
+                    return text[..end_offset].to_string();
+                }
+            }
+            _ => {}
+        }
+    }
+    text.to_string()
+}
+
+fn is_abbreviation(text_before: &str) -> bool {
+    let word = text_before
+        .rsplit(|c: char| c.is_whitespace() || c == '(')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    ABBREVIATIONS.contains(&word.as_str())
+}
+
+// `HTTPServer` and `My Class` become `http-server` and `my-class`.
+fn kebab_case(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !result.is_empty() && !result.ends_with('-') {
+                result.push('-');
+            }
+            continue;
+        }
+        let starts_word = c.is_uppercase()
+            && i > 0
+            && (chars[i - 1].is_lowercase()
+                || (chars[i - 1].is_uppercase()
+                    && chars.get(i + 1).is_some_and(|next| next.is_lowercase())));
+        if starts_word && !result.is_empty() && !result.ends_with('-') {
+            result.push('-');
+        }
+        result.extend(c.to_lowercase());
+    }
+    result.trim_end_matches('-').to_string()
+}
+
+// Cuts text longer than `length` characters, ending it with `…` so that it stays within
+// `length`.
+fn truncate(text: &str, length: usize) -> String {
+    if text.chars().count() <= length {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(length.saturating_sub(1)).collect();
+    format!("{}…", cut.trim_end())
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
 }
 
 fn categorize_constructs(
@@ -82,12 +1075,14 @@ fn categorize_constructs(
 
 pub fn generate_documentation(
     constructs: Vec<ConstructInfo>,
-    template: &str,
+    template: &Template,
+    config: &Config,
     output_dir: &Path,
     output_file: &Path,
 ) -> Result<(), io::Error> {
     let construct_map = categorize_constructs(constructs);
-    let expanded_template = expand_template(template, &construct_map);
+    let expanded_template = expand_template(template, &construct_map, config)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("template: {}", e)))?;
 
     let output_path = output_dir.join(output_file);
     let mut output_file = File::create(output_path)?;
@@ -100,10 +1095,19 @@ mod tests {
     use std::fs;
     use std::io::Write;
 
-    use crate::parser::AccessModifier;
+    use crate::doc_comment::parse_doc_comment;
+    use crate::parser::{parse_cs_source, AccessModifier, Modifiers, SourceSpan};
 
     use super::*;
 
+    fn expand(
+        template: &str,
+        construct_map: &HashMap<ConstructType, Vec<ConstructInfo>>,
+        config: &Config,
+    ) -> Result<String, TemplateError> {
+        expand_template(&Template::parse(template)?, construct_map, config)
+    }
+
     #[test]
     fn test_load_template() {
         let test_dir = PathBuf::from("_load_template_test_data");
@@ -117,10 +1121,168 @@ mod tests {
         }
 
         let loaded_template = load_template(&file_path).unwrap();
-        assert_eq!(loaded_template, template_content);
+        let construct_map = categorize_constructs(Vec::new());
+        assert_eq!(
+            expand_template(&loaded_template, &construct_map, &Config::default()).unwrap(),
+            "This is a test template.\n"
+        );
         fs::remove_dir_all(&test_dir).unwrap();
     }
 
+    #[test]
+    fn test_load_template_partials() {
+        let test_dir = PathBuf::from("_load_template_partials_test_data");
+        fs::create_dir_all(test_dir.join("partials")).unwrap();
+        let files = [
+            (
+                "template.md",
+                "{{> partials/header}}\n{{#each classes}}\n{{> entry}}\n{{/each}}\n{{> footer.txt}}",
+            ),
+            ("partials/header.md", "# API\n"),
+            ("entry.md", "- {{name}}{{#if summary}}: {{> summary}}{{/if}}\n"),
+            ("summary.md", "{{summary | first_sentence}}"),
+            ("footer.txt", "Generated.\n"),
+            ("cycle.md", "{{#each classes}}\n{{> a}}\n{{/each}}"),
+            ("a.md", "{{> b}}"),
+            ("b.md", "{{#if members}}{{> a}}{{/if}}"),
+            ("missing.md", "\n  {{> nothing}}"),
+        ];
+        for (name, content) in files {
+            fs::write(test_dir.join(name), content).unwrap();
+        }
+
+        let template = load_template(&test_dir.join("template.md")).unwrap();
+        let source = "/// <summary>Logs. Really.</summary>\nclass Logger { }\nclass Sink { }";
+        let construct_map = categorize_constructs(parse_cs_source(source, &[]));
+        assert_eq!(
+            expand_template(&template, &construct_map, &Config::default()).unwrap(),
+            "# API\n- Logger: Logs.\n- Sink\nGenerated.\n"
+        );
+        let error = load_template(&test_dir.join("cycle.md")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "{}:1:16: partials include each other: a > b > a",
+                test_dir.join("b.md").display()
+            )
+        );
+        let error = load_template(&test_dir.join("missing.md")).unwrap_err();
+        assert!(error.to_string().starts_with(&format!(
+            "{}:2:3: cannot load partial `nothing`",
+            test_dir.join("missing.md").display()
+        )));
+        fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_template_extends() {
+        let test_dir = PathBuf::from("_load_template_extends_test_data");
+        fs::create_dir_all(test_dir.join("layouts")).unwrap();
+        let files = [
+            (
+                "layouts/base.md",
+                "{{> header}}\n{% block classes %}\n{{#each classes}}\n- {{name}}\n{{/each}}\n{% endblock %}\n{% block footer %}\nGenerated.\n{% endblock %}\n",
+            ),
+            ("layouts/header.md", "# API\n"),
+            (
+                "project.md",
+                "{% extends \"layouts/base.md\" %}\n{% block footer %}\n{{> footer}}\n{% endblock %}\n",
+            ),
+            ("footer.md", "See the wiki.\n"),
+            ("a.md", "{% extends b.md %}"),
+            ("b.md", "{% extends a.md %}"),
+            ("orphan.md", "\n{% extends missing.md %}"),
+        ];
+        for (name, content) in files {
+            fs::write(test_dir.join(name), content).unwrap();
+        }
+
+        let template = load_template(&test_dir.join("project.md")).unwrap();
+        let construct_map = categorize_constructs(parse_cs_source("class Logger { }", &[]));
+        assert_eq!(
+            expand_template(&template, &construct_map, &Config::default()).unwrap(),
+            "# API\n- Logger\nSee the wiki.\n"
+        );
+        let error = load_template(&test_dir.join("a.md")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "{}:1:1: templates extend each other: {} > {} > {}",
+                test_dir.join("b.md").display(),
+                test_dir.join("a.md").display(),
+                test_dir.join("b.md").display(),
+                test_dir.join("a.md").display()
+            )
+        );
+        let error = load_template(&test_dir.join("orphan.md")).unwrap_err();
+        assert!(error.to_string().starts_with(&format!(
+            "{}:2:1: cannot load base template `missing.md`",
+            test_dir.join("orphan.md").display()
+        )));
+        fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[test]
+    fn test_check_template() {
+        let template = "\
+# [system_name] {{vars.anything}}
+{{#each constructs}}
+- [x] {{name}} ({{kind}}): {{summary | first_sentence}} [docs](https://host/{{anchor}})
+{{#each attributes}}{{name}}{{/each}}{{#each type_params}}{{name}}{{/each}}
+{{#each exceptions}}{{name}}{{/each}}{{#each base_types}}{{this}}{{/each}}
+{{#each members}}{{name}} {{#each params}}{{type}} {{../name}}{{/each}}{{/each}}
+{{#each enum_values}}{{#each attributes}}{{text}}{{/each}}{{/each}}
+{{/each}}
+[class_name] [one_sentence_summary] [if static] [source_link]
+";
+        let mut config = Config::default();
+        config
+            .vars
+            .insert("system_name".to_string(), "Inventory".to_string());
+        let template = Template::parse(template).unwrap();
+        assert_eq!(check_template(&template, &config), vec![]);
+
+        let template = "{{#each classes}}\n- [clas_name] {{nmae}} [if documented] {{summary | upper}}\n{{#each members}}{{params | truncate(x)}}{{/each}}\n{{/each}}\n[system_name]{{#each enums}}{{#each enum_values}}{{#each attributes}}{{nope}}{{/each}}{{/each}}{{/each}}";
+        let problems: Vec<String> = check_template(&Template::parse(template).unwrap(), &config)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                "2:3: unknown placeholder `[clas_name]`",
+                "2:15: unknown variable `nmae`",
+                "2:24: unknown condition `[if documented]`",
+                "2:40: unknown filter `upper`",
+                "3:18: `truncate`: `x` is not a length",
+                "5:70: unknown variable `nope`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_template_nested_lists() {
+        let template = "\
+{{#each structs}}{{#each members}}{{nmae}}{{/each}}{{/each}}
+{{#each interfaces}}{{#each params}}{{tpye}}{{/each}}{{/each}}
+{{#each classes}}{{#each enum_values}}{{bogus}}{{/each}}{{/each}}
+{{#each delegates}}{{#each members}}{{#each attributes}}{{#each arguments}}{{this}}{{/each}}{{/each}}{{/each}}{{/each}}
+";
+        let problems: Vec<String> =
+            check_template(&Template::parse(template).unwrap(), &Config::default())
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+        assert_eq!(
+            problems,
+            vec![
+                "1:35: unknown variable `nmae`",
+                "2:37: unknown variable `tpye`",
+                "3:39: unknown variable `bogus`",
+            ]
+        );
+    }
+
     #[test]
     fn test_expand_template() {
         let template = format!(
@@ -147,49 +1309,514 @@ mod tests {
 
         let constructs = vec![
             ConstructInfo {
-                name: "MyClass".to_string(),
-                docstring: Some("This is a class.".to_string()),
+                doc: parse_doc_comment(&["/// This is a class.".to_string()]),
                 access_modifier: AccessModifier::Public,
-                construct_type: ConstructType::Class,
+                ..ConstructInfo::new(ConstructType::Class, "MyClass")
             },
             ConstructInfo {
-                name: "MyStruct".to_string(),
-                docstring: Some("This is a struct.".to_string()),
+                doc: parse_doc_comment(&["/// This is a struct.".to_string()]),
                 access_modifier: AccessModifier::Public,
-                construct_type: ConstructType::Struct,
+                ..ConstructInfo::new(ConstructType::Struct, "MyStruct")
             },
             ConstructInfo {
-                name: "MyInterface".to_string(),
-                docstring: Some("This is an interface.".to_string()),
+                doc: parse_doc_comment(&["/// This is an interface.".to_string()]),
                 access_modifier: AccessModifier::Public,
-                construct_type: ConstructType::Interface,
+                ..ConstructInfo::new(ConstructType::Interface, "MyInterface")
             },
             ConstructInfo {
-                name: "MyEnum".to_string(),
-                docstring: Some("This is an enum.".to_string()),
+                doc: parse_doc_comment(&["/// This is an enum.".to_string()]),
                 access_modifier: AccessModifier::Public,
-                construct_type: ConstructType::Enum,
+                ..ConstructInfo::new(ConstructType::Enum, "MyEnum")
             },
         ];
 
         let construct_map = categorize_constructs(constructs);
-        let result = expand_template(&template, &construct_map);
+        let result = expand(&template, &construct_map, &Config::default()).unwrap();
         let expected = "
         # Documentation
 
         ## Classes
-        - **MyClass**: This is a class
+        - **MyClass**: This is a class.
 
         ## Structs
-        - **MyStruct**: This is a struct
+        - **MyStruct**: This is a struct.
 
         ## Interfaces
-        - **MyInterface**: This is an interface
+        - **MyInterface**: This is an interface.
 
         ## Enums
-        - **MyEnum**: This is an enum
+        - **MyEnum**: This is an enum.
         ";
 
         assert_eq!(result.trim(), expected.trim());
     }
+
+    #[test]
+    fn test_expand_template_namespace() {
+        let template = "- [class_name] in [namespace] ([full_name])";
+        let constructs = vec![
+            ConstructInfo {
+                namespace: Some("App.Logging".to_string()),
+                ..ConstructInfo::new(ConstructType::Class, "Logger")
+            },
+            ConstructInfo::new(ConstructType::Class, "Global"),
+        ];
+
+        let construct_map = categorize_constructs(constructs);
+        let result = expand(template, &construct_map, &Config::default()).unwrap();
+        assert_eq!(
+            result,
+            "- Logger in App.Logging (App.Logging.Logger)\n- Global in  (Global)\n"
+        );
+    }
+
+    #[test]
+    fn test_expand_template_nested_types() {
+        let template = "[indent]- [class_name] ([qualified_name])";
+        let constructs = vec![
+            ConstructInfo {
+                children: vec!["Inner".to_string()],
+                ..ConstructInfo::new(ConstructType::Class, "Outer")
+            },
+            ConstructInfo {
+                parent: Some("Outer".to_string()),
+                ..ConstructInfo::new(ConstructType::Class, "Inner")
+            },
+        ];
+
+        let construct_map = categorize_constructs(constructs);
+        let result = expand(template, &construct_map, &Config::default()).unwrap();
+        assert_eq!(result, "- Outer (Outer)\n  - Inner (Outer.Inner)\n");
+    }
+
+    #[test]
+    fn test_expand_template_type_hierarchy() {
+        let template = "[class_name][type_parameters] : [base_types] [constraints]";
+        let constructs = vec![ConstructInfo {
+            type_parameters: vec!["T".to_string()],
+            constraints: vec![TypeConstraint {
+                type_parameter: "T".to_string(),
+                constraints: vec!["IEntity".to_string(), "new()".to_string()],
+            }],
+            base_types: vec!["RepoBase".to_string(), "IRepo<T>".to_string()],
+            ..ConstructInfo::new(ConstructType::Class, "Repo")
+        }];
+
+        let construct_map = categorize_constructs(constructs);
+        let result = expand(template, &construct_map, &Config::default()).unwrap();
+        assert_eq!(
+            result,
+            "Repo<T> : RepoBase, IRepo<T> where T : IEntity, new()\n"
+        );
+    }
+
+    #[test]
+    fn test_expand_template_modifiers() {
+        let template = "- [class_name] ([modifiers])\n- [class_name] is abstract[if abstract]\n- [if !static][class_name] is not static [if documented]";
+        let words = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+        let constructs = vec![
+            ConstructInfo {
+                modifiers: Modifiers::from_keywords(&words("public abstract partial")),
+                ..ConstructInfo::new(ConstructType::Class, "Shape")
+            },
+            ConstructInfo {
+                modifiers: Modifiers::from_keywords(&words("static unsafe")),
+                ..ConstructInfo::new(ConstructType::Class, "Native")
+            },
+        ];
+
+        let construct_map = categorize_constructs(constructs);
+        let result = expand(template, &construct_map, &Config::default()).unwrap();
+        assert_eq!(
+            result,
+            "- Shape (abstract partial)\n- Native (static unsafe)\n- Shape is abstract\n- Shape is not static \n"
+        );
+    }
+
+    #[test]
+    fn test_expand_template_enum_values() {
+        let template = "- [enum_name] ([underlying_type])[if flags] [enum_values]";
+        let source = r#"
+/// <summary>Access rights.</summary>
+[Flags]
+enum Access : byte {
+    /// <summary>No access.</summary>
+    None = 0,
+    Read = 0x1,
+    Write,
+    All = Read | Write,
+    Next,
+}
+enum Plain { A, B }
+"#;
+        let construct_map = categorize_constructs(parse_cs_source(source, &[]));
+        let result = expand(template, &construct_map, &Config::default()).unwrap();
+        assert_eq!(
+            result,
+            "- Access (byte) \n\n| Name | Value | Description |\n| --- | --- | --- |\n| `None` | `0` | No access. |\n| `Read` | `0x1` |  |\n| `Write` | `2` |  |\n| `All` | `Read \\| Write` |  |\n| `Next` | `(Read \\| Write) + 1` |  |\n"
+        );
+    }
+
+    #[test]
+    fn test_expand_template_attributes() {
+        let template = "- [class_name] [attributes]\n> [if obsolete]**Deprecated** [class_name]: [obsolete_message]";
+        let source = r#"
+[Serializable]
+[Obsolete("Use \"Logger\" instead", false)]
+class OldLogger { }
+class Logger { }
+"#;
+        let construct_map = categorize_constructs(parse_cs_source(source, &[]));
+        let result = expand(template, &construct_map, &Config::default()).unwrap();
+        assert_eq!(
+            result,
+            "- OldLogger [Serializable] [Obsolete(\"Use \\\"Logger\\\" instead\", false)]\n- Logger \n> **Deprecated** OldLogger: Use \"Logger\" instead\n"
+        );
+    }
+
+    #[test]
+    fn test_expand_template_source_location() {
+        let template = "- [class_name] ([file]:[line]) [source_link]";
+        let constructs = vec![ConstructInfo {
+            span: Some(SourceSpan {
+                file: PathBuf::from("Logging").join("Logger.cs"),
+                start_line: 7,
+                start_column: 5,
+                end_line: 30,
+                end_column: 5,
+            }),
+            ..ConstructInfo::new(ConstructType::Class, "Logger")
+        }];
+        let config = Config {
+            source_link: Some(
+                "https://host/repo/blob/{rev}/{file}#L{line}-L{end_line}".to_string(),
+            ),
+            revision: Some("v1.2".to_string()),
+            ..Config::default()
+        };
+
+        let construct_map = categorize_constructs(constructs);
+        assert_eq!(
+            expand(template, &construct_map, &config).unwrap(),
+            "- Logger (Logging/Logger.cs:7) https://host/repo/blob/v1.2/Logging/Logger.cs#L7-L30\n"
+        );
+        assert_eq!(
+            expand(template, &construct_map, &Config::default()).unwrap(),
+            "- Logger (Logging/Logger.cs:7) \n"
+        );
+    }
+
+    #[test]
+    fn test_expand_template_doc_sections() {
+        let template = "[class_name]: [remarks] Returns [returns]. Params: [params]. [example]";
+        let lines: Vec<String> = [
+            "/// <summary>Summary.</summary>",
+            "/// <remarks>Some remarks.</remarks>",
+            "/// <param name=\"a\">First.</param>",
+            "/// <param name=\"b\">Second.</param>",
+            "/// <returns>A value</returns>",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+        let constructs = vec![ConstructInfo {
+            doc: parse_doc_comment(&lines),
+            ..ConstructInfo::new(ConstructType::Class, "MyClass")
+        }];
+
+        let construct_map = categorize_constructs(constructs);
+        let result = expand(template, &construct_map, &Config::default()).unwrap();
+        assert_eq!(
+            result,
+            "MyClass: Some remarks. Returns A value. Params: `a`: First., `b`: Second.. [example]\n"
+        );
+    }
+
+    #[test]
+    fn test_expand_template_renders_links() {
+        let template = "[class_name]: [summary]";
+        let lines = vec![
+            "/// <summary>Writes to a <see cref=\"T:App.Sink{T}\"/> via <see cref=\"M:App.Sink`1.Write(`0)\"/>.</summary>"
+                .to_string(),
+        ];
+        let constructs = vec![
+            ConstructInfo {
+                doc: parse_doc_comment(&lines),
+                ..ConstructInfo::new(ConstructType::Class, "Logger")
+            },
+            ConstructInfo {
+                namespace: Some("App".to_string()),
+                ..ConstructInfo::new(ConstructType::Interface, "Sink")
+            },
+        ];
+
+        let construct_map = categorize_constructs(constructs);
+        let result = expand(template, &construct_map, &Config::default()).unwrap();
+        assert_eq!(
+            result,
+            "Logger: Writes to a [`Sink<T>`](#appsink) via [`Sink.Write`](#appsink).\n"
+        );
+    }
+
+    #[test]
+    fn test_link_targets_with_same_names() {
+        let constructs = vec![
+            ConstructInfo {
+                namespace: Some("App.Logging".to_string()),
+                ..ConstructInfo::new(ConstructType::Class, "Logger")
+            },
+            ConstructInfo {
+                namespace: Some("App.Diagnostics".to_string()),
+                ..ConstructInfo::new(ConstructType::Class, "Logger")
+            },
+            ConstructInfo {
+                namespace: Some("App".to_string()),
+                ..ConstructInfo::new(ConstructType::Interface, "Sink")
+            },
+        ];
+        let links = LinkTargets::new(&categorize_constructs(constructs));
+        assert_eq!(
+            links.resolve("T:App.Logging.Logger"),
+            Some("applogginglogger".to_string())
+        );
+        assert_eq!(
+            links.resolve("App.Diagnostics.Logger.Log"),
+            Some("appdiagnosticslogger".to_string())
+        );
+        assert_eq!(links.resolve("Logger"), None);
+        assert_eq!(links.resolve("Sink"), Some("appsink".to_string()));
+    }
+
+    #[test]
+    fn test_expand_template_blocks() {
+        let template = "\
+# API
+{{#each classes}}
+## {{name}}
+{{summary}}
+{{#each members}}
+- `{{name}}({{#each params}}{{type}} {{name}}; {{/each}})`: {{summary}}
+{{/each}}
+{{/each}}
+{{#each enums}}
+## {{name}}
+{{#each enum_values}}
+- {{name}} = {{value}}
+{{/each}}
+{{/each}}
+- [interface_name]
+";
+        let source = r#"
+/// <summary>Writes messages.</summary>
+class Logger {
+    /// <summary>Logs a message.</summary>
+    /// <param name="level">The level.</param>
+    public void Log(int level, string message) { }
+    public void Flush() { }
+}
+enum Level { Info, Error = 4, Fatal }
+interface ISink { }
+"#;
+        let construct_map = categorize_constructs(parse_cs_source(source, &[]));
+        let result = expand(template, &construct_map, &Config::default()).unwrap();
+        assert_eq!(
+            result,
+            "# API\n## Logger\nWrites messages.\n- `Log(int level; string message; )`: Logs a message.\n- `Flush()`: \n## Level\n- Info = 0\n- Error = 4\n- Fatal = 5\n- ISink\n"
+        );
+        let error = expand("{{#each classes}}", &construct_map, &Config::default());
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "1:1: `{{#each}}` is never closed"
+        );
+    }
+
+    #[test]
+    fn test_expand_template_conditions() {
+        let template = "\
+{{#each classes}}
+{{#if access_modifier == \"protected internal\"}}
+- {{name}} is protected internal
+{{else if is_static}}
+- {{name}} is static{{#if !remarks}} without remarks{{/if}}
+{{/if}}
+{{/each}}
+{{#each enums}}
+- {{name}}
+{{else}}
+No enums
+{{/each}}
+";
+        let source = r#"
+protected internal class Shared { }
+/// <summary>Helpers.</summary>
+public static class Helpers { }
+class Hidden { }
+"#;
+        let construct_map = categorize_constructs(parse_cs_source(source, &[]));
+        let result = expand(template, &construct_map, &Config::default()).unwrap();
+        assert_eq!(
+            result,
+            "- Shared is protected internal\n- Helpers is static without remarks\nNo enums\n"
+        );
+    }
+
+    #[test]
+    fn test_expand_template_filters() {
+        let template = "\
+{{#each classes}}
+- [{{name}}](#{{name | kebab_case}}): {{summary | first_sentence}}
+  {{summary | truncate(12) | escape_html}} {{base_types | kebab_case | join(\" / \")}}
+{{/each}}
+";
+        let source = r#"
+/// <summary>Serves HTTP requests. Uses <c>Stream</c> & sockets.</summary>
+class HTTPServer : ServerBase, IDisposable { }
+"#;
+        let construct_map = categorize_constructs(parse_cs_source(source, &[]));
+        let result = expand(template, &construct_map, &Config::default()).unwrap();
+        assert_eq!(
+            result,
+            "- [HTTPServer](#http-server): Serves HTTP requests.\n  Serves HTTP… server-base / i-disposable\n"
+        );
+        let error = expand("{{classes | truncate}}", &construct_map, &Config::default());
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "1:1: `truncate`: expected a length, e.g. `truncate(80)`"
+        );
+        assert_eq!(truncate("<a> & b", 80), "<a> & b");
+        assert_eq!(
+            escape_html("<a href=\"x\">'&'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+        );
+        assert_eq!(kebab_case("My Class_Name2"), "my-class-name2");
+    }
+
+    #[test]
+    fn test_expand_template_sorting() {
+        let template = "\
+{{#each classes | sort(access_modifier, name)}}{{access_modifier}} {{name}}, {{/each}}
+{{#each constructs | sort(source)}}{{name}}, {{/each}}
+{{#each constructs | sort(namespace) | group_by(namespace)}}
+## {{name}}{{#each items}} {{name}}{{/each}}
+{{/each}}
+{{#each classes | group_by(folder)}}{{name}}:{{#each items}} {{name}}{{/each}}; {{/each}}
+{{#each classes | group_by(region)}}{{#if name}}{{name}}{{else}}none{{/if}}:{{#each items}} {{name}}{{/each}}; {{/each}}
+";
+        let sources = [
+            ("src/Zeta.cs", "namespace B { internal class Zeta { } public struct Point { } }"),
+            (
+                "src/Net/Client.cs",
+                "namespace A {\n#region Net\npublic class client { }\npublic class Client { }\n#endregion\n}",
+            ),
+            ("Alpha.cs", "namespace B { public class Alpha { } }"),
+        ];
+        let mut constructs = Vec::new();
+        for (file, source) in sources {
+            for mut construct in parse_cs_source(source, &[]) {
+                construct.span.as_mut().unwrap().file = PathBuf::from(file);
+                constructs.push(construct);
+            }
+        }
+        let construct_map = categorize_constructs(constructs);
+        let result = expand(template, &construct_map, &Config::default()).unwrap();
+        assert_eq!(
+            result,
+            "public Alpha, public Client, public client, internal Zeta, \n\
+             Alpha, client, Client, Zeta, Point, \n\
+             ## A client Client\n\
+             ## B Zeta Alpha Point\n\
+             src: Zeta; src/Net: client Client; : Alpha; \n\
+             none: Zeta Alpha; Net: client Client; \n"
+        );
+        let error = expand(
+            "{{#each classes | sort(nmae)}}{{/each}}",
+            &construct_map,
+            &Config::default(),
+        );
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "1:1: `sort`: the items have no field `nmae`"
+        );
+        let items = [Value::object([("name", "A".into())]), Value::object([])];
+        assert!(check_field(&items, "name").is_err());
+        assert!(check_field(&items[..1], "name").is_ok());
+    }
+
+    #[test]
+    fn test_first_sentence() {
+        let cases = [
+            ("Parses files. Then more.", "Parses files."),
+            ("Version 1.2 parser. Next.", "Version 1.2 parser."),
+            (
+                "Uses System.IO, e.g. streams. Next.",
+                "Uses System.IO, e.g. streams.",
+            ),
+            (
+                "Reads a `File.Open. Call` (i.e. files). Next",
+                "Reads a `File.Open. Call` (i.e. files).",
+            ),
+            (
+                "See [`Sink.Write. X`](#sink). Next",
+                "See [`Sink.Write. X`](#sink).",
+            ),
+            ("Really?! Yes.", "Really?!"),
+            ("He said \"stop.\" Then left.", "He said \"stop.\""),
+            ("Ends at 3 p.m. on weekdays", "Ends at 3 p.m. on weekdays"),
+            ("No period\n\nSecond paragraph.", "No period"),
+            ("  Trimmed.  ", "Trimmed."),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(first_sentence(text), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_expand_template_vars() {
+        let template = "\
+# {{vars.system_name}}
+{{#if vars.overview}}
+{{vars.overview}}
+{{/if}}
+[system_name] by [author]
+{{#each classes}}
+- {{name}}: {{summary}}
+{{/each}}
+";
+        let source = "/// <summary>Part of [system_name].</summary>\nclass Item { }";
+        let construct_map = categorize_constructs(parse_cs_source(source, &[]));
+        let mut config = Config::default();
+        let error = expand(template, &construct_map, &config).unwrap_err();
+        assert_eq!(error.position, Position { line: 1, column: 3 });
+        assert!(error
+            .message
+            .starts_with("template variables `system_name`, `author` are not set"));
+
+        config
+            .vars
+            .insert("system_name".to_string(), "Inventory".to_string());
+        let error = expand(template, &construct_map, &config).unwrap_err();
+        assert_eq!(
+            error.position,
+            Position {
+                line: 5,
+                column: 18
+            }
+        );
+        assert!(error
+            .message
+            .starts_with("template variable `author` is not set"));
+
+        config.vars.insert("author".to_string(), "Ops".to_string());
+        assert_eq!(
+            expand(template, &construct_map, &config).unwrap(),
+            "# Inventory\nInventory by Ops\n- Item: Part of [system_name].\n"
+        );
+        config
+            .vars
+            .insert("overview".to_string(), "Tracks stock.".to_string());
+        assert_eq!(
+            expand(template, &construct_map, &config).unwrap(),
+            "# Inventory\nTracks stock.\nInventory by Ops\n- Item: Part of [system_name].\n"
+        );
+    }
 }
@@ -0,0 +1,563 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::doc_comment::{strip_cref, strip_generic_arity, DocComment};
+use crate::parser::{ConstructInfo, MemberInfo};
+
+// Identifies a documented item: a construct, or one of its members.
+type DocKey = (usize, Option<usize>);
+
+// Replaces `<inheritdoc/>` documentation with the documentation of the base type or member it
+// refers to, keeping any sections that were written out explicitly.
+pub fn resolve_inheritdoc(constructs: &mut [ConstructInfo]) {
+    let resolved: Vec<(DocKey, Option<DocComment>)> = {
+        let mut resolver = InheritDocResolver::new(constructs);
+        let keys: Vec<DocKey> = constructs
+            .iter()
+            .enumerate()
+            .flat_map(|(ci, construct)| {
+                std::iter::once((ci, None))
+                    .chain((0..construct.members.len()).map(move |mi| (ci, Some(mi))))
+            })
+            .filter(|key| {
+                resolver
+                    .doc(*key)
+                    .is_some_and(|doc| doc.inheritdoc.is_some())
+            })
+            .collect();
+        keys.into_iter()
+            .map(|key| (key, resolver.resolve(key)))
+            .collect()
+    };
+
+    for ((ci, mi), doc) in resolved {
+        match mi {
+            Some(mi) => constructs[ci].members[mi].doc = doc,
+            None => constructs[ci].doc = doc,
+        }
+    }
+}
+
+struct InheritDocResolver<'a> {
+    constructs: &'a [ConstructInfo],
+    // Construct indices by full, qualified and simple name
+    by_name: HashMap<String, Vec<usize>>,
+    resolved: HashMap<DocKey, Option<DocComment>>,
+    in_progress: HashSet<DocKey>,
+    // Warnings already printed, as the same base type is looked up for every member
+    warned: HashSet<String>,
+}
+
+impl<'a> InheritDocResolver<'a> {
+    fn new(constructs: &'a [ConstructInfo]) -> Self {
+        let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, construct) in constructs.iter().enumerate() {
+            let mut names = vec![
+                construct.full_name(),
+                construct.qualified_name(),
+                construct.name.clone(),
+            ];
+            names.dedup();
+            for name in names {
+                by_name.entry(name).or_default().push(i);
+            }
+        }
+        Self {
+            constructs,
+            by_name,
+            resolved: HashMap::new(),
+            in_progress: HashSet::new(),
+            warned: HashSet::new(),
+        }
+    }
+
+    fn warn(&mut self, message: String) {
+        if self.warned.insert(message.clone()) {
+            eprintln!("Warning: {}", message);
+        }
+    }
+
+    fn doc(&self, (ci, mi): DocKey) -> Option<&'a DocComment> {
+        let construct = &self.constructs[ci];
+        match mi {
+            Some(mi) => construct.members[mi].doc.as_ref(),
+            None => construct.doc.as_ref(),
+        }
+    }
+
+    fn describe(&self, (ci, mi): DocKey) -> String {
+        let construct = &self.constructs[ci];
+        match mi {
+            Some(mi) => format!("{}.{}", construct.full_name(), construct.members[mi].name),
+            None => construct.full_name(),
+        }
+    }
+
+    fn resolve(&mut self, key: DocKey) -> Option<DocComment> {
+        if let Some(resolved) = self.resolved.get(&key) {
+            return resolved.clone();
+        }
+        let doc = self.doc(key)?;
+        let Some(inheritdoc) = &doc.inheritdoc else {
+            return Some(doc.clone());
+        };
+        // Closing a cycle: the rest of it inherits this doc as written, so the cycle is only
+        // reported here
+        if !self.in_progress.insert(key) {
+            eprintln!(
+                "Warning: cyclic <inheritdoc/> involving {}",
+                self.describe(key)
+            );
+            return Some(DocComment {
+                inheritdoc: None,
+                ..doc.clone()
+            });
+        }
+
+        let source = match &inheritdoc.cref {
+            Some(cref) => self.find_cref(cref, key.0),
+            None => self.find_inherited(key),
+        };
+        let inherited = source.and_then(|source| self.resolve(source));
+        self.in_progress.remove(&key);
+
+        let result = match inherited {
+            Some(inherited) => doc.inherit_from(&inherited),
+            None => {
+                eprintln!(
+                    "Warning: could not resolve <inheritdoc/> for {}",
+                    self.describe(key)
+                );
+                DocComment {
+                    inheritdoc: None,
+                    ..doc.clone()
+                }
+            }
+        };
+        self.resolved.insert(key, Some(result.clone()));
+        Some(result)
+    }
+
+    // Looks a type name up as seen from the construct at `context`, preferring types in the same
+    // or an enclosing namespace. A name that matches several types is reported and left
+    // unresolved rather than guessing.
+    fn find_type(&mut self, name: &str, context: usize) -> Option<usize> {
+        let name = strip_generic_arity(name.trim_start_matches("global::"));
+        let mut namespace = self.constructs[context].namespace.clone();
+        let mut found = None;
+        while let Some(current) = namespace {
+            found = self.by_name.get(&format!("{}.{}", current, name));
+            if found.is_some() {
+                break;
+            }
+            namespace = current.rfind('.').map(|i| current[..i].to_string());
+        }
+        match found.or_else(|| self.by_name.get(&name))?.as_slice() {
+            [only] => Some(*only),
+            found => {
+                let candidates: Vec<String> = found
+                    .iter()
+                    .map(|&i| self.constructs[i].full_name())
+                    .collect();
+                self.warn(format!(
+                    "ambiguous name `{}` in <inheritdoc/>, could be {}",
+                    name,
+                    candidates.join(", ")
+                ));
+                None
+            }
+        }
+    }
+
+    // Finds the type or member a `cref` refers to. A member `cref` without parameters must name a
+    // single member, e.g. `Save` when there is only one overload of it.
+    fn find_cref(&mut self, cref: &str, context: usize) -> Option<DocKey> {
+        let name = strip_cref(cref);
+        let parameters = cref_parameters(cref);
+        if parameters.is_none() {
+            if let Some(found) = self.find_type(name, context) {
+                return Some((found, None));
+            }
+        }
+        let (type_name, member_name) = match name.rfind('.') {
+            Some(dot) => (&name[..dot], &name[dot + 1..]),
+            None => ("", name),
+        };
+        let found = if type_name.is_empty() {
+            context
+        } else {
+            self.find_type(type_name, context)?
+        };
+        let member_name = strip_generic_arity(member_name);
+        let constructs = self.constructs;
+        let members = &constructs[found].members;
+        let matches: Vec<usize> = (0..members.len())
+            .filter(|&mi| members[mi].name == member_name)
+            .filter(|&mi| match &parameters {
+                Some(parameters) => matches_cref_parameters(&members[mi], parameters),
+                None => true,
+            })
+            .collect();
+        match matches[..] {
+            [mi] => Some((found, Some(mi))),
+            [] => None,
+            _ => {
+                self.warn(format!(
+                    "ambiguous cref `{}` in <inheritdoc/>, give the parameters of the overload",
+                    cref
+                ));
+                None
+            }
+        }
+    }
+
+    // Walks the base types breadth first, looking for the first documented base type or, for a
+    // member, the first documented member with the same signature. A member with the same number
+    // of parameters is only taken when none has the same parameter types.
+    fn find_inherited(&mut self, (ci, mi): DocKey) -> Option<DocKey> {
+        let constructs = self.constructs;
+        let member = mi.map(|mi| &constructs[ci].members[mi]);
+        let mut visited = HashSet::from([ci]);
+        // Along with each type, what the type parameters of it stand for in the type the walk
+        // started from, e.g. `T` is `User` in `IRepository<T>` for `Repository : IRepository<User>`
+        let mut queue = VecDeque::from([(ci, HashMap::new())]);
+        while let Some((current, arguments)) = queue.pop_front() {
+            for base_type in &constructs[current].base_types {
+                let Some(base) = self.find_type(base_type, current) else {
+                    continue;
+                };
+                if !visited.insert(base) {
+                    continue;
+                }
+                let arguments = type_arguments(base_type, &constructs[base], &arguments);
+                let candidate = match member {
+                    Some(member) => find_overload(&constructs[base].members, member, &arguments)
+                        .map(|mi| (base, Some(mi))),
+                    None => Some((base, None)),
+                };
+                if let Some(candidate) = candidate.filter(|c| self.doc(*c).is_some()) {
+                    return Some(candidate);
+                }
+                queue.push_back((base, arguments));
+            }
+        }
+        None
+    }
+}
+
+// The overload of `member` among the members of a base type, with the type parameters of the base
+// type filled in with `arguments`.
+fn find_overload(
+    members: &[MemberInfo],
+    member: &MemberInfo,
+    arguments: &HashMap<String, String>,
+) -> Option<usize> {
+    let same_shape = |m: &MemberInfo| {
+        m.name == member.name
+            && m.member_type == member.member_type
+            && m.parameters.len() == member.parameters.len()
+    };
+    let same_parameters = |m: &MemberInfo| {
+        m.parameters
+            .iter()
+            .zip(&member.parameters)
+            .all(|(a, b)| same_type(&substitute(&a.type_name, arguments), &b.type_name))
+    };
+    members
+        .iter()
+        .position(|m| same_shape(m) && same_parameters(m))
+        .or_else(|| members.iter().position(same_shape))
+}
+
+// Whether the parameter types of a member are the ones of a `cref`. References to type
+// parameters such as `` `0 `` match any type.
+fn matches_cref_parameters(member: &MemberInfo, parameters: &[String]) -> bool {
+    member.parameters.len() == parameters.len()
+        && member
+            .parameters
+            .iter()
+            .zip(parameters)
+            .all(|(p, cref_type)| cref_type.starts_with('`') || same_type(&p.type_name, cref_type))
+}
+
+// What the type parameters of `base` stand for, given the `base_type` it is referred to as, e.g.
+// `T` is `User` for `IRepository<User>`. The type arguments are themselves in terms of the type
+// parameters of the referring type, which `outer` fills in.
+fn type_arguments(
+    base_type: &str,
+    base: &ConstructInfo,
+    outer: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    let (Some(open), Some(close)) = (base_type.find('<'), base_type.rfind('>')) else {
+        return HashMap::new();
+    };
+    let parameters = base.type_parameters.iter().map(|parameter| {
+        // Drop variance such as `in T`
+        parameter
+            .rsplit(' ')
+            .next()
+            .unwrap_or(parameter)
+            .to_string()
+    });
+    parameters
+        .zip(split_arguments(&base_type[open + 1..close]))
+        .map(|(parameter, argument)| (parameter, substitute(argument, outer)))
+        .collect()
+}
+
+// Splits type arguments or `cref` parameters at the commas that are not nested in brackets.
+fn split_arguments(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '<' | '{' | '(' | '[' => depth += 1,
+            '>' | '}' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(text[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(text[start..].trim());
+    parts
+}
+
+// Replaces the type parameter names in a type name, e.g. `List<T>` becomes `List<User>`.
+fn substitute(type_name: &str, arguments: &HashMap<String, String>) -> String {
+    let mut result = String::new();
+    let mut identifier = String::new();
+    for c in type_name.chars().chain(std::iter::once(' ')) {
+        if c.is_alphanumeric() || c == '_' {
+            identifier.push(c);
+            continue;
+        }
+        result.push_str(arguments.get(&identifier).unwrap_or(&identifier));
+        identifier.clear();
+        result.push(c);
+    }
+    result.pop();
+    result
+}
+
+// The parameter types of a member `cref` such as `Save(System.Int32, List{T})`, `None` if it has
+// no parameter list.
+fn cref_parameters(cref: &str) -> Option<Vec<String>> {
+    let open = cref.find('(')?;
+    let close = cref.rfind(')')?;
+    let parameters = cref[open + 1..close].trim();
+    if parameters.is_empty() {
+        return Some(Vec::new());
+    }
+    Some(
+        split_arguments(parameters)
+            .into_iter()
+            .map(|p| p.replace('{', "<").replace('}', ">"))
+            .collect(),
+    )
+}
+
+// Compares type names regardless of whitespace, namespaces and keyword aliases, so that `int`
+// matches `System.Int32`.
+fn same_type(a: &str, b: &str) -> bool {
+    normalize_type(a) == normalize_type(b)
+}
+
+fn normalize_type(type_name: &str) -> String {
+    let mut result = String::new();
+    let mut identifier = String::new();
+    let mut chars = type_name.trim_end_matches('@').chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_alphanumeric() || c == '_' {
+            identifier.push(c);
+            continue;
+        }
+        if c == '.' {
+            // Only the last part of a qualified name counts
+            identifier.clear();
+            continue;
+        }
+        result.push_str(type_alias(&identifier));
+        identifier.clear();
+        if !c.is_whitespace() {
+            result.push(c);
+        }
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+    }
+    result.push_str(type_alias(&identifier));
+    result
+}
+
+fn type_alias(name: &str) -> &str {
+    match name {
+        "bool" => "Boolean",
+        "byte" => "Byte",
+        "sbyte" => "SByte",
+        "char" => "Char",
+        "decimal" => "Decimal",
+        "double" => "Double",
+        "float" => "Single",
+        "int" => "Int32",
+        "uint" => "UInt32",
+        "long" => "Int64",
+        "ulong" => "UInt64",
+        "short" => "Int16",
+        "ushort" => "UInt16",
+        "object" => "Object",
+        "string" => "String",
+        "nint" => "IntPtr",
+        "nuint" => "UIntPtr",
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc_comment::plain_text;
+    use crate::parser::parse_cs_source;
+
+    fn summary_of(doc: &Option<DocComment>) -> Option<String> {
+        doc.as_ref()
+            .and_then(|doc| doc.summary.as_deref())
+            .map(plain_text)
+    }
+
+    #[test]
+    fn test_resolve_inheritdoc_from_base_types() {
+        let source = r#"
+namespace App {
+    /// <summary>Stores entities.</summary>
+    public interface IRepository<T> {
+        /// <summary>Saves an entity.</summary>
+        /// <param name="entity">The entity.</param>
+        void Save(T entity);
+        /// <summary>Saves many.</summary>
+        void Save(T entity, int count);
+    }
+    /// <summary>Base repository.</summary>
+    public abstract class RepositoryBase { }
+    /// <inheritdoc/>
+    public class Repository : RepositoryBase, IRepository<User> {
+        /// <inheritdoc/>
+        /// <remarks>Writes to disk.</remarks>
+        public void Save(User entity) { }
+        /// <inheritdoc cref="IRepository{T}.Save(`0)"/>
+        public void Store(User entity) { }
+    }
+}
+"#;
+        let mut constructs = parse_cs_source(source, &[]);
+        resolve_inheritdoc(&mut constructs);
+
+        let repository = &constructs[2];
+        assert_eq!(
+            summary_of(&repository.doc),
+            Some("Base repository.".to_string())
+        );
+        let save = repository.members[0].doc.as_ref().unwrap();
+        assert_eq!(
+            summary_of(&repository.members[0].doc),
+            Some("Saves an entity.".to_string())
+        );
+        assert_eq!(
+            plain_text(save.remarks.as_ref().unwrap()),
+            "Writes to disk."
+        );
+        assert_eq!(save.params.len(), 1);
+        assert_eq!(save.inheritdoc, None);
+        assert_eq!(
+            summary_of(&repository.members[1].doc),
+            Some("Saves an entity.".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_inheritdoc_overloads() {
+        let source = r#"
+namespace App {
+    public interface IStore<T> {
+        /// <summary>Saves by id.</summary>
+        void Save(int id);
+        /// <summary>Saves an item.</summary>
+        void Save(T item);
+        /// <summary>Saves a name.</summary>
+        void Save(string name);
+    }
+    public class Store : IStore<Item> {
+        /// <inheritdoc/>
+        public void Save(Item item) { }
+        /// <inheritdoc/>
+        public void Save(string name) { }
+        /// <inheritdoc cref="IStore{T}.Save(System.Int32)"/>
+        public void Put(int id) { }
+        /// <inheritdoc cref="IStore{T}.Save"/>
+        public void Add(Item item) { }
+    }
+}
+namespace App.Logging {
+    /// <summary>Logs to a file.</summary>
+    public class Logger { }
+}
+namespace App.Diagnostics {
+    /// <summary>Logs to the console.</summary>
+    public class Logger { }
+}
+namespace Other {
+    /// <inheritdoc cref="Logger"/>
+    public class Sink { }
+    /// <inheritdoc cref="App.Logging.Logger"/>
+    public class FileSink { }
+}
+"#;
+        let mut constructs = parse_cs_source(source, &[]);
+        resolve_inheritdoc(&mut constructs);
+
+        let store = &constructs[1];
+        let summaries: Vec<Option<String>> =
+            store.members.iter().map(|m| summary_of(&m.doc)).collect();
+        assert_eq!(
+            summaries,
+            vec![
+                Some("Saves an item.".to_string()),
+                Some("Saves a name.".to_string()),
+                Some("Saves by id.".to_string()),
+                None,
+            ]
+        );
+        assert_eq!(store.members[3].doc.as_ref().unwrap().inheritdoc, None);
+        assert_eq!(summary_of(&constructs[4].doc), None);
+        assert_eq!(
+            summary_of(&constructs[5].doc),
+            Some("Logs to a file.".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_inheritdoc_cycles() {
+        let source = r#"
+/// <inheritdoc cref="B"/>
+class A { }
+/// <inheritdoc cref="A"/>
+/// <remarks>B remarks.</remarks>
+class B { }
+/// <inheritdoc/>
+class C : Unknown { }
+"#;
+        let mut constructs = parse_cs_source(source, &[]);
+        resolve_inheritdoc(&mut constructs);
+
+        let a = constructs[0].doc.as_ref().unwrap();
+        assert_eq!(plain_text(a.remarks.as_ref().unwrap()), "B remarks.");
+        assert_eq!(a.inheritdoc, None);
+        let b = constructs[1].doc.as_ref().unwrap();
+        assert_eq!(plain_text(b.remarks.as_ref().unwrap()), "B remarks.");
+        assert_eq!(b.inheritdoc, None);
+        let c = constructs[2].doc.as_ref().unwrap();
+        assert_eq!(summary_of(&constructs[2].doc), None);
+        assert_eq!(c.inheritdoc, None);
+    }
+}
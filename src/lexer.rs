@@ -0,0 +1,515 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Identifier,
+    Number,
+    String,
+    Char,
+    Punctuation,
+    DocComment,
+    Directive,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+    pub span: Span,
+}
+
+impl Token {
+    pub fn is(&self, text: &str) -> bool {
+        self.kind != TokenKind::String && self.kind != TokenKind::Char && self.text == text
+    }
+
+    pub fn is_identifier(&self) -> bool {
+        self.kind == TokenKind::Identifier
+    }
+}
+
+// Operators that are kept as a single token. `>>` and `>=` are deliberately missing so that
+// closing generic argument lists always produce one `>` per level.
+const COMPOUND_PUNCTUATION: [&str; 20] = [
+    "=>", "==", "!=", "<=", "&&", "||", "??", "::", "++", "--", "->", "+=", "-=", "*=", "/=", "%=",
+    "&=", "|=", "^=", "?.",
+];
+
+pub fn tokenize(source: &str) -> Vec<Token> {
+    let mut lexer = Lexer::new(source);
+    lexer.run();
+    lexer.tokens
+}
+
+struct Lexer<'a> {
+    source: &'a str,
+    chars: Vec<(usize, char)>,
+    pos: usize,
+    line: usize,
+    column: usize,
+    at_line_start: bool,
+    tokens: Vec<Token>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            chars: source.char_indices().collect(),
+            pos: 0,
+            line: 1,
+            column: 1,
+            at_line_start: true,
+            tokens: Vec::new(),
+        }
+    }
+
+    fn peek(&self, ahead: usize) -> Option<char> {
+        self.chars.get(self.pos + ahead).map(|(_, c)| *c)
+    }
+
+    fn peek_is(&self, ahead: usize, expected: char) -> bool {
+        self.peek(ahead) == Some(expected)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn bump_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.bump();
+        }
+    }
+
+    fn position(&self) -> Position {
+        Position {
+            offset: self
+                .chars
+                .get(self.pos)
+                .map_or(self.source.len(), |(offset, _)| *offset),
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn count_run(&self, c: char) -> usize {
+        let mut count = 0;
+        while self.peek_is(count, c) {
+            count += 1;
+        }
+        count
+    }
+
+    fn push(&mut self, kind: TokenKind, start: Position) {
+        let end = self.position();
+        self.tokens.push(Token {
+            kind,
+            text: self.source[start.offset..end.offset].to_string(),
+            span: Span { start, end },
+        });
+    }
+
+    fn run(&mut self) {
+        while let Some(c) = self.peek(0) {
+            if c == '\n' {
+                self.bump();
+                self.at_line_start = true;
+                continue;
+            }
+            if c.is_whitespace() {
+                self.bump();
+                continue;
+            }
+
+            let start = self.position();
+            if c == '#' && self.at_line_start {
+                self.skip_directive();
+                let mut token_end = self.position();
+                let text = self.source[start.offset..token_end.offset].trim_end();
+                token_end.offset = start.offset + text.len();
+                token_end.column = start.column + text.chars().count();
+                self.tokens.push(Token {
+                    kind: TokenKind::Directive,
+                    text: text.to_string(),
+                    span: Span {
+                        start,
+                        end: token_end,
+                    },
+                });
+                self.skip_to_line_end();
+                continue;
+            }
+            self.at_line_start = false;
+
+            if c == '/' && self.peek_is(1, '/') {
+                let is_doc = self.peek_is(2, '/') && !self.peek_is(3, '/');
+                self.skip_to_line_end();
+                if is_doc {
+                    self.push(TokenKind::DocComment, start);
+                }
+            } else if c == '/' && self.peek_is(1, '*') {
+                let is_doc = self.peek_is(2, '*') && !self.peek_is(3, '/');
+                self.skip_block_comment();
+                if is_doc {
+                    self.push(TokenKind::DocComment, start);
+                }
+            } else if self.skip_string_literal() {
+                self.push(TokenKind::String, start);
+            } else if c == '\'' {
+                self.skip_char_literal();
+                self.push(TokenKind::Char, start);
+            } else if c.is_ascii_digit()
+                || (c == '.' && self.peek(1).is_some_and(|n| n.is_ascii_digit()))
+            {
+                self.skip_number();
+                self.push(TokenKind::Number, start);
+            } else if is_identifier_start(c)
+                || (c == '@' && self.peek(1).is_some_and(is_identifier_start))
+            {
+                self.bump();
+                while self.peek(0).is_some_and(is_identifier_part) {
+                    self.bump();
+                }
+                self.push(TokenKind::Identifier, start);
+            } else {
+                let compound = COMPOUND_PUNCTUATION
+                    .iter()
+                    .find(|op| op.chars().enumerate().all(|(i, oc)| self.peek_is(i, oc)));
+                self.bump_n(compound.map_or(1, |op| op.len()));
+                self.push(TokenKind::Punctuation, start);
+            }
+        }
+    }
+
+    fn skip_to_line_end(&mut self) {
+        while self.peek(0).is_some_and(|c| c != '\n') {
+            self.bump();
+        }
+    }
+
+    fn skip_directive(&mut self) {
+        // A directive runs to the end of the line; a trailing `//` comment is not part of it
+        while let Some(c) = self.peek(0) {
+            if c == '\n' || (c == '/' && self.peek_is(1, '/')) {
+                break;
+            }
+            self.bump();
+        }
+    }
+
+    fn skip_block_comment(&mut self) {
+        // C# block comments do not nest: the first `*/` closes the comment
+        self.bump_n(2);
+        while self.peek(0).is_some() {
+            if self.peek_is(0, '*') && self.peek_is(1, '/') {
+                self.bump_n(2);
+                return;
+            }
+            self.bump();
+        }
+    }
+
+    fn skip_char_literal(&mut self) {
+        self.bump();
+        while let Some(c) = self.peek(0) {
+            match c {
+                '\\' => self.bump_n(2),
+                '\'' => {
+                    self.bump();
+                    return;
+                }
+                '\n' => return,
+                _ => {
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    fn skip_number(&mut self) {
+        let is_hex = self.peek_is(0, '0') && matches!(self.peek(1), Some('x' | 'X'));
+        while let Some(c) = self.peek(0) {
+            let is_exponent_sign = (c == '+' || c == '-')
+                && !is_hex
+                && matches!(self.chars.get(self.pos - 1), Some((_, 'e' | 'E')));
+            if c.is_ascii_alphanumeric()
+                || c == '_'
+                || (c == '.' && self.peek(1).is_some_and(|n| n.is_ascii_digit()))
+                || is_exponent_sign
+            {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    // Consumes any kind of string literal starting at the cursor and reports whether one was found.
+    fn skip_string_literal(&mut self) -> bool {
+        let dollars = self.count_run('$');
+        let verbatim_at = match (self.peek(dollars), self.peek(dollars + 1)) {
+            (Some('@'), Some('"')) => Some(dollars),
+            (Some('"'), _) => None,
+            _ if dollars == 0 && self.peek_is(0, '@') && self.peek_is(1, '$') => {
+                let inner_dollars = 1 + self.count_run_from(2, '$');
+                if self.peek_is(1 + inner_dollars, '"') {
+                    self.bump_n(1 + inner_dollars);
+                    self.skip_quoted(true, inner_dollars);
+                    return true;
+                }
+                return false;
+            }
+            _ => return false,
+        };
+
+        self.bump_n(dollars);
+        if verbatim_at.is_some() {
+            self.bump();
+            self.skip_quoted(true, dollars);
+        } else if self.count_run('"') >= 3 {
+            self.skip_raw(dollars);
+        } else {
+            self.skip_quoted(false, dollars);
+        }
+        true
+    }
+
+    fn count_run_from(&self, from: usize, c: char) -> usize {
+        let mut count = 0;
+        while self.peek_is(from + count, c) {
+            count += 1;
+        }
+        count
+    }
+
+    // Regular and verbatim strings, optionally interpolated. The cursor is on the opening quote.
+    fn skip_quoted(&mut self, verbatim: bool, dollars: usize) {
+        self.bump();
+        while let Some(c) = self.peek(0) {
+            match c {
+                '\\' if !verbatim => self.bump_n(2),
+                '"' if verbatim && self.peek_is(1, '"') => self.bump_n(2),
+                '"' => {
+                    self.bump();
+                    return;
+                }
+                '\n' if !verbatim => return,
+                '{' | '}' if dollars > 0 && self.peek_is(1, c) => self.bump_n(2),
+                '{' if dollars > 0 => {
+                    self.bump();
+                    self.skip_interpolation_hole();
+                }
+                _ => {
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    // Raw strings (`"""..."""`), optionally interpolated with one or more `$`.
+    fn skip_raw(&mut self, dollars: usize) {
+        let quotes = self.count_run('"');
+        self.bump_n(quotes);
+        while let Some(c) = self.peek(0) {
+            if c == '"' {
+                let run = self.count_run('"');
+                if run >= quotes {
+                    self.bump_n(quotes);
+                    return;
+                }
+                self.bump_n(run);
+            } else if c == '{' && dollars > 0 {
+                let run = self.count_run('{');
+                self.bump_n(run);
+                if run >= dollars {
+                    self.skip_interpolation_hole();
+                    let closing = self.count_run('}');
+                    self.bump_n(closing.min(dollars.saturating_sub(1)));
+                }
+            } else {
+                self.bump();
+            }
+        }
+    }
+
+    // Skips the expression inside `{...}` of an interpolated string, including the closing brace.
+    fn skip_interpolation_hole(&mut self) {
+        let mut depth = 0;
+        while let Some(c) = self.peek(0) {
+            match c {
+                '}' if depth == 0 => {
+                    self.bump();
+                    return;
+                }
+                '{' | '(' | '[' => {
+                    depth += 1;
+                    self.bump();
+                }
+                '}' | ')' | ']' => {
+                    depth -= 1;
+                    self.bump();
+                }
+                '\'' => self.skip_char_literal(),
+                '/' if self.peek_is(1, '*') => self.skip_block_comment(),
+                _ => {
+                    if !self.skip_string_literal() {
+                        self.bump();
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_identifier_part(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds_and_texts(source: &str) -> Vec<(TokenKind, String)> {
+        tokenize(source)
+            .into_iter()
+            .map(|t| (t.kind, t.text))
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_declaration() {
+        let tokens = kinds_and_texts("public class MyClass<T> : Base { }");
+        let texts: Vec<&str> = tokens.iter().map(|(_, t)| t.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec!["public", "class", "MyClass", "<", "T", ">", ":", "Base", "{", "}"]
+        );
+        assert_eq!(tokens[2].0, TokenKind::Identifier);
+        assert_eq!(tokens[3].0, TokenKind::Punctuation);
+    }
+
+    #[test]
+    fn test_tokenize_spans() {
+        let tokens = tokenize("class A\n{\n    int x;\n}");
+        let x = tokens.iter().find(|t| t.text == "x").unwrap();
+        assert_eq!(x.span.start.line, 3);
+        assert_eq!(x.span.start.column, 9);
+        assert_eq!(x.span.end.column, 10);
+        assert_eq!(x.span.start.offset, 18);
+    }
+
+    #[test]
+    fn test_tokenize_comments() {
+        let tokens = kinds_and_texts(
+            "// class A\n/* class B /* still comment */ int Field;\n/// <summary>Doc</summary>\n//// not doc",
+        );
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Identifier, "int".to_string()),
+                (TokenKind::Identifier, "Field".to_string()),
+                (TokenKind::Punctuation, ";".to_string()),
+                (
+                    TokenKind::DocComment,
+                    "/// <summary>Doc</summary>".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_string_literals() {
+        let source = r#"a = "class \" A"; b = @"c:\path ""class"""; c = 'x'; d = '\''; e = '"';"#;
+        let strings: Vec<String> = tokenize(source)
+            .into_iter()
+            .filter(|t| t.kind == TokenKind::String || t.kind == TokenKind::Char)
+            .map(|t| t.text)
+            .collect();
+        assert_eq!(
+            strings,
+            vec![
+                r#""class \" A""#,
+                r#"@"c:\path ""class""""#,
+                "'x'",
+                r"'\''",
+                "'\"'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_interpolated_strings() {
+        let source = r#"s = $"{(a ? "}" : "class")} {{x}} {b:N2}"; t = $@"{c}"""; u = @$"{d}";"#;
+        let tokens = tokenize(source);
+        let strings: Vec<&str> = tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::String)
+            .map(|t| t.text.as_str())
+            .collect();
+        assert_eq!(
+            strings,
+            vec![
+                r#"$"{(a ? "}" : "class")} {{x}} {b:N2}""#,
+                r#"$@"{c}""""#,
+                r#"@$"{d}""#,
+            ]
+        );
+        assert_eq!(tokens.last().unwrap().text, ";");
+    }
+
+    #[test]
+    fn test_tokenize_raw_strings() {
+        let source =
+            "a = \"\"\"\n  class \"quoted\" \"\"\n  \"\"\"; b = $$\"\"\"{{{x}}} {y}\"\"\";";
+        let tokens = tokenize(source);
+        let strings: Vec<&str> = tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::String)
+            .map(|t| t.text.as_str())
+            .collect();
+        assert_eq!(
+            strings,
+            vec![
+                "\"\"\"\n  class \"quoted\" \"\"\n  \"\"\"",
+                "$$\"\"\"{{{x}}} {y}\"\"\"",
+            ]
+        );
+        assert_eq!(tokens.iter().filter(|t| t.text == ";").count(), 2);
+    }
+
+    #[test]
+    fn test_tokenize_directives_and_numbers() {
+        let tokens = kinds_and_texts("#if DEBUG // comment\nx = 1.5e-3f + 0x1E+2;\n#endif");
+        assert_eq!(tokens[0], (TokenKind::Directive, "#if DEBUG".to_string()));
+        assert_eq!(tokens[3], (TokenKind::Number, "1.5e-3f".to_string()));
+        assert_eq!(tokens[5], (TokenKind::Number, "0x1E".to_string()));
+        assert_eq!(
+            tokens.last().unwrap(),
+            &(TokenKind::Directive, "#endif".to_string())
+        );
+    }
+}
@@ -1,36 +1,115 @@
+use std::path::Path;
+use std::process;
+
 use structopt::StructOpt;
 
-use crate::cli::Cli;
-use crate::documentation::{generate_documentation, load_template};
+use crate::cli::{CheckTemplate, Cli};
+use crate::config::{load_config, vars_from_env, Config};
+use crate::documentation::{check_template, generate_documentation, load_template};
 use crate::parser::{find_cs_files, parse_cs_files};
 
 mod cli;
+mod config;
+mod doc_comment;
 mod documentation;
+mod inheritdoc;
+mod lexer;
+mod markdown;
 mod parser;
+mod preprocessor;
+mod template;
 
 fn main() {
+    if std::env::args_os()
+        .nth(1)
+        .is_some_and(|arg| arg == "check-template")
+    {
+        check_template_command(CheckTemplate::from_iter(std::env::args_os().skip(1)));
+        return;
+    }
+
     let args = Cli::from_args();
     println!("Package directory: {:?}", args.package_dir);
     println!("Template file: {:?}", args.template_file);
     println!("Output directory: {:?}", args.output_dir);
     println!("Output file: {:?}", args.output_file);
 
+    let Some(mut config) = load_settings(args.config.as_deref(), args.vars) else {
+        process::exit(1);
+    };
+    config.defines.extend(args.defines);
+    if args.source_link.is_some() {
+        config.source_link = args.source_link;
+    }
+    if args.revision.is_some() {
+        config.revision = args.revision;
+    }
+
     let cs_files = find_cs_files(&args.package_dir);
-    let constructs = parse_cs_files(cs_files);
+    let constructs = parse_cs_files(&args.package_dir, cs_files, &config.defines);
 
     // Load the template
     let template = match load_template(&args.template_file) {
         Ok(t) => t,
         Err(e) => {
             eprintln!("Failed to load template: {}", e);
-            return;
+            process::exit(1);
         }
     };
+    for problem in check_template(&template, &config) {
+        eprintln!("Warning: {}", problem);
+    }
 
     // Generate the documentation
-    if let Err(e) =
-        generate_documentation(constructs, &template, &args.output_dir, &args.output_file)
-    {
+    if let Err(e) = generate_documentation(
+        constructs,
+        &template,
+        &config,
+        &args.output_dir,
+        &args.output_file,
+    ) {
         eprintln!("Failed to generate documentation: {}", e);
+        process::exit(1);
+    }
+}
+
+// Loads the configuration file, if any, and the template variables. Command line options add to
+// or override the environment, which overrides the configuration file.
+fn load_settings(config_file: Option<&Path>, vars: Vec<(String, String)>) -> Option<Config> {
+    let mut config = match config_file {
+        Some(config_file) => match load_config(config_file) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Failed to load config: {}", e);
+                return None;
+            }
+        },
+        None => Config::default(),
+    };
+    config.vars.extend(vars_from_env(std::env::vars()));
+    config.vars.extend(vars);
+    Some(config)
+}
+
+// Exits with an error status if the template cannot be loaded or refers to unknown variables,
+// filters or placeholders.
+fn check_template_command(args: CheckTemplate) {
+    let Some(config) = load_settings(args.config.as_deref(), args.vars) else {
+        process::exit(1);
+    };
+    let template = match load_template(&args.template_file) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+    let problems = check_template(&template, &config);
+    for problem in &problems {
+        eprintln!("{}", problem);
+    }
+    if !problems.is_empty() {
+        process::exit(1);
     }
+    println!("{}: no problems found", args.template_file.display());
 }
@@ -0,0 +1,328 @@
+use crate::doc_comment::{plain_text, strip_cref, DocNode};
+
+// Renders documentation comment content as Markdown. `resolve_link` maps a `cref` to the anchor of
+// the documented entry it refers to, if there is one.
+pub fn render_markdown(nodes: &[DocNode], resolve_link: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut out = String::new();
+    render_nodes(nodes, resolve_link, &mut out);
+    normalize(&out)
+}
+
+// Builds a GitHub style heading anchor, e.g. `Outer.Inner` becomes `outerinner`.
+pub fn anchor(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+fn render_nodes(
+    nodes: &[DocNode],
+    resolve_link: &dyn Fn(&str) -> Option<String>,
+    out: &mut String,
+) {
+    for node in nodes {
+        let DocNode::Element { name, children, .. } = node else {
+            if let DocNode::Text(text) = node {
+                push_collapsed(out, text);
+            }
+            continue;
+        };
+        match name.as_str() {
+            "c" => {
+                out.push('`');
+                out.push_str(&plain_text(children));
+                out.push('`');
+            }
+            "code" => {
+                out.push_str("\n\n```csharp\n");
+                out.push_str(&dedent(&raw_text(children)));
+                out.push_str("\n```\n\n");
+            }
+            "para" => {
+                out.push_str("\n\n");
+                render_nodes(children, resolve_link, out);
+                out.push_str("\n\n");
+            }
+            "br" => out.push('\n'),
+            "b" | "strong" => wrap(children, "**", resolve_link, out),
+            "i" | "em" => wrap(children, "*", resolve_link, out),
+            "paramref" | "typeparamref" => {
+                out.push('`');
+                out.push_str(node.attribute("name").unwrap_or_default());
+                out.push('`');
+            }
+            "see" | "seealso" | "a" => render_reference(node, children, resolve_link, out),
+            "list" => render_list(node, children, resolve_link, out),
+            _ => render_nodes(children, resolve_link, out),
+        }
+    }
+}
+
+fn wrap(
+    children: &[DocNode],
+    marker: &str,
+    resolve_link: &dyn Fn(&str) -> Option<String>,
+    out: &mut String,
+) {
+    out.push_str(marker);
+    out.push_str(&inline(children, resolve_link));
+    out.push_str(marker);
+}
+
+fn render_reference(
+    node: &DocNode,
+    children: &[DocNode],
+    resolve_link: &dyn Fn(&str) -> Option<String>,
+    out: &mut String,
+) {
+    let text = inline(children, resolve_link);
+    if let Some(href) = node.attribute("href") {
+        let text = if text.is_empty() { href } else { &text };
+        out.push_str(&format!("[{}]({})", text, href));
+    } else if let Some(langword) = node.attribute("langword") {
+        out.push_str(&format!("`{}`", langword));
+    } else if let Some(cref) = node.attribute("cref") {
+        let text = if text.is_empty() {
+            format!("`{}`", cref_display_name(cref))
+        } else {
+            text
+        };
+        match resolve_link(cref) {
+            Some(anchor) => out.push_str(&format!("[{}](#{})", text, anchor)),
+            None => out.push_str(&text),
+        }
+    } else {
+        out.push_str(&text);
+    }
+}
+
+fn render_list(
+    node: &DocNode,
+    children: &[DocNode],
+    resolve_link: &dyn Fn(&str) -> Option<String>,
+    out: &mut String,
+) {
+    let list_type = node.attribute("type").unwrap_or("bullet");
+    let mut header = None;
+    let mut rows = Vec::new();
+    for child in children {
+        let DocNode::Element { name, children, .. } = child else {
+            continue;
+        };
+        let term = find_child(children, "term").map(|t| inline(t, resolve_link));
+        let description = match find_child(children, "description") {
+            Some(description) => inline(description, resolve_link),
+            None if term.is_none() => inline(children, resolve_link),
+            None => String::new(),
+        };
+        match name.as_str() {
+            "listheader" => header = Some((term, description)),
+            "item" => rows.push((term, description)),
+            _ => {}
+        }
+    }
+
+    out.push_str("\n\n");
+    if list_type == "table" {
+        let (term, description) =
+            header.unwrap_or((Some("Term".to_string()), "Description".to_string()));
+        out.push_str(&format!(
+            "| {} | {} |\n| --- | --- |\n",
+            term.unwrap_or_default(),
+            description
+        ));
+        for (term, description) in rows {
+            out.push_str(&format!(
+                "| {} | {} |\n",
+                term.unwrap_or_default(),
+                description
+            ));
+        }
+    } else {
+        for (i, (term, description)) in rows.into_iter().enumerate() {
+            let marker = if list_type == "number" {
+                format!("{}.", i + 1)
+            } else {
+                "-".to_string()
+            };
+            match term {
+                Some(term) if description.is_empty() => {
+                    out.push_str(&format!("{} {}\n", marker, term))
+                }
+                Some(term) => out.push_str(&format!("{} **{}**: {}\n", marker, term, description)),
+                None => out.push_str(&format!("{} {}\n", marker, description)),
+            }
+        }
+    }
+    out.push('\n');
+}
+
+fn find_child<'n>(children: &'n [DocNode], element: &str) -> Option<&'n [DocNode]> {
+    children.iter().find_map(|child| match child {
+        DocNode::Element { name, children, .. } if name == element => Some(children.as_slice()),
+        _ => None,
+    })
+}
+
+// Renders content that has to stay on a single line, such as list items and link texts.
+fn inline(nodes: &[DocNode], resolve_link: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut out = String::new();
+    render_nodes(nodes, resolve_link, &mut out);
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// `T:App.Logging.Logger` is shown as `Logger`, member references such as `M:App.Logger.Log(System.String)`
+// keep their type: `Logger.Log`.
+fn cref_display_name(cref: &str) -> String {
+    let is_member = matches!(cref.get(..2), Some("M:" | "P:" | "F:" | "E:"));
+    let mut name = strip_cref(cref).replace('{', "<").replace('}', ">");
+    while let Some(tick) = name.find('`') {
+        let digits = name[tick..]
+            .find(|c: char| c != '`' && !c.is_ascii_digit())
+            .unwrap_or(name.len() - tick);
+        name.replace_range(tick..tick + digits, "");
+    }
+    let segments: Vec<&str> = name.split('.').collect();
+    let keep = if is_member { 2 } else { 1 };
+    segments[segments.len().saturating_sub(keep)..].join(".")
+}
+
+fn raw_text(nodes: &[DocNode]) -> String {
+    let mut text = String::new();
+    for node in nodes {
+        match node {
+            DocNode::Text(t) => text.push_str(t),
+            DocNode::Element { children, .. } => text.push_str(&raw_text(children)),
+        }
+    }
+    text
+}
+
+fn dedent(code: &str) -> String {
+    let lines: Vec<&str> = code.lines().skip_while(|l| l.trim().is_empty()).collect();
+    let lines = &lines[..lines
+        .iter()
+        .rposition(|l| !l.trim().is_empty())
+        .map_or(0, |i| i + 1)];
+    let indent = lines
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|l| l.get(indent..).unwrap_or("").trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn push_collapsed(out: &mut String, text: &str) {
+    let mut last_was_space = out.ends_with(' ') || out.ends_with('\n') || out.is_empty();
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !last_was_space {
+                out.push(' ');
+            }
+            last_was_space = true;
+        } else {
+            out.push(c);
+            last_was_space = false;
+        }
+    }
+}
+
+// Trims stray spaces around line breaks and collapses runs of blank lines, leaving code blocks
+// untouched.
+fn normalize(text: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_code = false;
+    for line in text.split('\n') {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            lines.push(line.trim().to_string());
+            continue;
+        }
+        if in_code {
+            lines.push(line.to_string());
+            continue;
+        }
+        let line = line.trim();
+        if line.is_empty() && lines.last().is_some_and(|l| l.is_empty()) {
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+    lines.join("\n").trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc_comment::parse_doc_comment;
+
+    fn render(summary: &str) -> String {
+        let lines = vec![format!("/// <summary>{}</summary>", summary)];
+        let doc = parse_doc_comment(&lines).unwrap();
+        let resolve = |cref: &str| {
+            strip_cref(cref)
+                .starts_with("App.Logger")
+                .then(|| "logger".to_string())
+        };
+        render_markdown(doc.summary.as_ref().unwrap(), &resolve)
+    }
+
+    #[test]
+    fn test_render_inline_elements() {
+        assert_eq!(
+            render(
+                "Use <c>Log()</c> on <see cref=\"T:App.Logger\"/> with <paramref name=\"level\"/>."
+            ),
+            "Use `Log()` on [`Logger`](#logger) with `level`."
+        );
+        assert_eq!(
+            render("Returns <see langword=\"null\"/> or a <see cref=\"System.IO.Stream\"/>."),
+            "Returns `null` or a `Stream`."
+        );
+        assert_eq!(
+            render("See <see href=\"https://example.com\">the docs</see> and <see cref=\"M:App.Logger.Log(System.String)\"/>."),
+            "See [the docs](https://example.com) and [`Logger.Log`](#logger)."
+        );
+    }
+
+    #[test]
+    fn test_render_paragraphs_and_lists() {
+        assert_eq!(
+            render("First.<para>Second\n   paragraph.</para><list type=\"bullet\"><item><description>One</description></item><item><term>Two</term><description>Second item</description></item></list>"),
+            "First.\n\nSecond paragraph.\n\n- One\n- **Two**: Second item"
+        );
+        assert_eq!(
+            render("<list type=\"number\"><item>A</item><item>B</item></list>"),
+            "1. A\n2. B"
+        );
+        assert_eq!(
+            render("<list type=\"table\"><listheader><term>Key</term><description>Meaning</description></listheader><item><term>a</term><description>first</description></item></list>"),
+            "| Key | Meaning |\n| --- | --- |\n| a | first |"
+        );
+    }
+
+    #[test]
+    fn test_render_code_block() {
+        assert_eq!(
+            render("Example:<code>\n    var x = 1;\n    if (x > 0)\n        x++;\n</code>"),
+            "Example:\n\n```csharp\nvar x = 1;\nif (x > 0)\n    x++;\n```"
+        );
+    }
+
+    #[test]
+    fn test_anchor() {
+        assert_eq!(anchor("Outer.Inner"), "outerinner");
+        assert_eq!(anchor("My Class_1"), "my-class_1");
+    }
+}
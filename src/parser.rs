@@ -1,63 +1,441 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
+use std::fmt;
 use std::fs::File;
 use std::io::Read;
-use std::path::PathBuf;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-use regex::Regex;
 use serde::Serialize;
-use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 use strum_macros::{Display, EnumString};
 use walkdir::WalkDir;
 
-#[derive(Debug, Serialize, PartialEq, EnumString, Display, EnumIter)]
+use crate::doc_comment::{parse_doc_comment, strip_generic_arity, DocComment};
+use crate::inheritdoc::resolve_inheritdoc;
+use crate::lexer::{tokenize, Token, TokenKind};
+use crate::preprocessor::preprocess;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, EnumString, Display, EnumIter)]
 #[strum(serialize_all = "snake_case")]
 pub enum AccessModifier {
     Public,
     Private,
     Protected,
     Internal,
+    #[strum(serialize = "protected internal")]
+    ProtectedInternal,
+    #[strum(serialize = "private protected")]
+    PrivateProtected,
+}
+
+impl AccessModifier {
+    // Lower is more accessible, so that sorting puts `public` first and `private` last.
+    pub fn rank(&self) -> u8 {
+        match self {
+            AccessModifier::Public => 0,
+            AccessModifier::ProtectedInternal => 1,
+            AccessModifier::Protected => 2,
+            AccessModifier::Internal => 3,
+            AccessModifier::PrivateProtected => 4,
+            AccessModifier::Private => 5,
+        }
+    }
+}
+
+// The declaration modifiers other than the access modifier, e.g. `abstract` or `static`.
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq)]
+pub struct Modifiers {
+    pub is_abstract: bool,
+    pub is_sealed: bool,
+    pub is_static: bool,
+    pub is_readonly: bool,
+    pub is_partial: bool,
+    pub is_unsafe: bool,
+    pub is_virtual: bool,
+    pub is_override: bool,
+    pub is_extern: bool,
+    pub is_new: bool,
+    pub is_const: bool,
+    pub is_volatile: bool,
+    pub is_async: bool,
+    pub is_required: bool,
+    pub is_file: bool,
+    pub is_ref: bool,
 }
 
+impl Modifiers {
+    pub fn from_keywords(keywords: &[String]) -> Self {
+        let mut modifiers = Self::default();
+        for keyword in keywords {
+            if let Some(flag) = modifiers.flag_mut(keyword) {
+                *flag = true;
+            }
+        }
+        modifiers
+    }
+
+    fn flag_mut(&mut self, keyword: &str) -> Option<&mut bool> {
+        Some(match keyword {
+            "abstract" => &mut self.is_abstract,
+            "sealed" => &mut self.is_sealed,
+            "static" => &mut self.is_static,
+            "readonly" => &mut self.is_readonly,
+            "partial" => &mut self.is_partial,
+            "unsafe" => &mut self.is_unsafe,
+            "virtual" => &mut self.is_virtual,
+            "override" => &mut self.is_override,
+            "extern" => &mut self.is_extern,
+            "new" => &mut self.is_new,
+            "const" => &mut self.is_const,
+            "volatile" => &mut self.is_volatile,
+            "async" => &mut self.is_async,
+            "required" => &mut self.is_required,
+            "file" => &mut self.is_file,
+            "ref" => &mut self.is_ref,
+            _ => return None,
+        })
+    }
+
+    // Looks a modifier up by its keyword, returning `None` for words that are not modifiers.
+    pub fn has(&self, keyword: &str) -> Option<bool> {
+        let mut modifiers = *self;
+        modifiers.flag_mut(keyword).map(|flag| *flag)
+    }
+
+    // The keywords that are set, in the conventional C# order.
+    pub fn keywords(&self) -> Vec<&'static str> {
+        MODIFIERS[4..]
+            .iter()
+            .copied()
+            .filter(|keyword| self.has(keyword) == Some(true))
+            .collect()
+    }
+
+    // Every modifier keyword with whether it is set.
+    pub fn flags(&self) -> Vec<(&'static str, bool)> {
+        MODIFIERS[4..]
+            .iter()
+            .map(|keyword| (*keyword, self.has(keyword) == Some(true)))
+            .collect()
+    }
+
+    fn union(&mut self, other: Modifiers) {
+        for keyword in other.keywords() {
+            if let Some(flag) = self.flag_mut(keyword) {
+                *flag = true;
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
 pub struct ConstructInfo {
-    pub docstring: Option<String>,
+    pub doc: Option<DocComment>,
     pub access_modifier: AccessModifier,
     pub construct_type: ConstructType,
     pub name: String,
+    pub members: Vec<MemberInfo>,
+    pub parameters: Vec<Parameter>,
+    pub return_type: Option<String>,
+    pub namespace: Option<String>,
+    // Qualified name of the containing type, relative to the namespace
+    pub parent: Option<String>,
+    // Names of the types declared directly inside this one
+    pub children: Vec<String>,
+    // Generic type parameters, including their variance, e.g. `out T`
+    pub type_parameters: Vec<String>,
+    pub constraints: Vec<TypeConstraint>,
+    // Everything after the `:` of the declaration, in source order
+    pub base_types: Vec<String>,
+    pub base_class: Option<String>,
+    pub interfaces: Vec<String>,
+    pub modifiers: Modifiers,
+    // Enum members in declaration order, with the underlying type from `enum E : byte`
+    pub enum_members: Vec<EnumMember>,
+    pub underlying_type: Option<String>,
+    pub attributes: Vec<Attribute>,
+    // The innermost `#region` the construct is declared in
+    pub region: Option<String>,
+    // Where the construct is declared; the first part for partial types
+    pub span: Option<SourceSpan>,
+    // Every file that contributes a declaration of this construct
+    pub files: Vec<PathBuf>,
+}
+
+impl ConstructInfo {
+    pub fn new(construct_type: ConstructType, name: &str) -> Self {
+        Self {
+            doc: None,
+            access_modifier: AccessModifier::Private,
+            construct_type,
+            name: name.to_string(),
+            members: Vec::new(),
+            parameters: Vec::new(),
+            return_type: None,
+            namespace: None,
+            parent: None,
+            children: Vec::new(),
+            type_parameters: Vec::new(),
+            constraints: Vec::new(),
+            base_types: Vec::new(),
+            base_class: None,
+            interfaces: Vec::new(),
+            modifiers: Modifiers::default(),
+            enum_members: Vec::new(),
+            underlying_type: None,
+            attributes: Vec::new(),
+            region: None,
+            span: None,
+            files: Vec::new(),
+        }
+    }
+
+    // Adds another part of a partial type to this one.
+    fn merge(&mut self, part: ConstructInfo) {
+        if self.doc.is_none() {
+            self.doc = part.doc;
+        }
+        // Parts that specify an access modifier must agree, so any non-default one wins
+        let default = if self.parent.is_some() {
+            AccessModifier::Private
+        } else {
+            AccessModifier::Internal
+        };
+        if self.access_modifier == default {
+            self.access_modifier = part.access_modifier;
+        }
+        for member in part.members {
+            match self
+                .members
+                .iter_mut()
+                .find(|m| m.signature == member.signature)
+            {
+                // Partial methods appear once as a declaration and once as an implementation
+                Some(existing) => {
+                    if existing.doc.is_none() {
+                        existing.doc = member.doc;
+                    }
+                }
+                None => self.members.push(member),
+            }
+        }
+        // `abstract`, `sealed` and `static` only have to be given on one of the parts
+        self.modifiers.union(part.modifiers);
+        if self.type_parameters.is_empty() {
+            self.type_parameters = part.type_parameters;
+        }
+        for constraint in part.constraints {
+            if !self.constraints.contains(&constraint) {
+                self.constraints.push(constraint);
+            }
+        }
+        for attribute in part.attributes {
+            if !self.attributes.contains(&attribute) {
+                self.attributes.push(attribute);
+            }
+        }
+        for base_type in part.base_types {
+            if !self.base_types.contains(&base_type) {
+                self.base_types.push(base_type);
+            }
+        }
+        for child in part.children {
+            if !self.children.contains(&child) {
+                self.children.push(child);
+            }
+        }
+        for file in part.files {
+            if !self.files.contains(&file) {
+                self.files.push(file);
+            }
+        }
+    }
+
+    // Looks up a boolean property usable in template conditions: `flags` or a modifier keyword.
+    pub fn condition(&self, name: &str) -> Option<bool> {
+        match name {
+            "flags" => Some(self.is_flags()),
+            "obsolete" => Some(self.obsolete().is_some()),
+            _ => self.modifiers.has(name),
+        }
+    }
+
+    pub fn is_flags(&self) -> bool {
+        self.construct_type == ConstructType::Enum && self.attributes.iter().any(|a| a.is("Flags"))
+    }
+
+    pub fn obsolete(&self) -> Option<&Attribute> {
+        self.attributes.iter().find(|a| a.is("Obsolete"))
+    }
+
+    pub fn qualified_name(&self) -> String {
+        match &self.parent {
+            Some(parent) => format!("{}.{}", parent, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    pub fn full_name(&self) -> String {
+        match &self.namespace {
+            Some(namespace) => format!("{}.{}", namespace, self.qualified_name()),
+            None => self.qualified_name(),
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.parent
+            .as_ref()
+            .map_or(0, |parent| parent.split('.').count())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemberInfo {
+    pub doc: Option<DocComment>,
+    pub access_modifier: AccessModifier,
+    pub member_type: MemberType,
+    pub name: String,
+    pub type_name: Option<String>,
+    pub parameters: Vec<Parameter>,
+    pub modifiers: Modifiers,
+    pub attributes: Vec<Attribute>,
+    pub region: Option<String>,
+    pub accessors: Vec<String>,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum MemberType {
+    Method,
+    Property,
+    Field,
+    Event,
+    Constructor,
+    Indexer,
+    Operator,
+}
+
+// An attribute such as `[Obsolete("Use Save instead")]`, with its arguments as written.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Attribute {
+    pub name: String,
+    pub arguments: Vec<String>,
+    // An explicit target such as `return` in `[return: NotNull]`
+    pub target: Option<String>,
+}
+
+impl Attribute {
+    // Matches `Obsolete`, `ObsoleteAttribute` and `System.Obsolete` alike.
+    pub fn is(&self, name: &str) -> bool {
+        let simple_name = self.name.rsplit('.').next().unwrap_or_default();
+        simple_name.strip_suffix("Attribute").unwrap_or(simple_name) == name
+    }
+
+    // The first string argument without its quotes, e.g. the message of `[Obsolete("...")]`.
+    pub fn message(&self) -> Option<String> {
+        self.arguments.iter().find_map(|argument| {
+            argument
+                .trim_start_matches('@')
+                .strip_prefix('"')
+                .and_then(|text| text.strip_suffix('"'))
+                .map(|text| text.replace("\\\"", "\""))
+        })
+    }
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[")?;
+        if let Some(target) = &self.target {
+            write!(f, "{}: ", target)?;
+        }
+        write!(f, "{}", self.name)?;
+        if !self.arguments.is_empty() {
+            write!(f, "({})", self.arguments.join(", "))?;
+        }
+        write!(f, "]")
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct EnumMember {
+    pub doc: Option<DocComment>,
+    pub attributes: Vec<Attribute>,
+    pub name: String,
+    // The explicit value or expression, e.g. `1 << 2`
+    pub value: Option<String>,
+}
+
+// The location of a declaration, from the start of its attributes to the end of its closing `}`
+// or `;`. Lines and columns start at 1, and the end column is the one after the last character.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SourceSpan {
+    // Relative to the package directory
+    pub file: PathBuf,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+// A `where T : ...` clause of a generic declaration.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TypeConstraint {
+    pub type_parameter: String,
+    pub constraints: Vec<String>,
 }
 
-#[derive(Debug, Eq, Hash, Clone, Serialize, PartialEq, EnumIter)]
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Parameter {
+    pub name: String,
+    pub type_name: String,
+    pub modifier: Option<String>,
+    pub default_value: Option<String>,
+}
+
+#[derive(Debug, Eq, Hash, Clone, Serialize, PartialEq, Display, EnumIter)]
+#[strum(serialize_all = "snake_case")]
 pub enum ConstructType {
     Class,
     Struct,
     Enum,
     Interface,
+    Record,
+    RecordStruct,
+    Delegate,
 }
 
 impl ConstructType {
     pub fn as_lowercase(&self) -> String {
-        format!("{:?}", self).to_lowercase()
+        self.to_string()
     }
 
     pub fn as_placeholder(&self, suffix: &str) -> String {
         format!("[{}{}]", self.as_lowercase(), suffix)
     }
-}
 
-impl AccessModifier {
-    pub fn variants_as_regex() -> String {
-        let variants: Vec<String> = AccessModifier::iter().map(|v| v.to_string()).collect();
-        let pattern = variants.join("|");
-        format!(r"(?m)^\s*({})", pattern)
+    // The name of the template list of all constructs of this type, e.g. `classes`.
+    pub fn as_plural(&self) -> String {
+        match self {
+            ConstructType::Class => "classes".to_string(),
+            _ => format!("{}s", self.as_lowercase()),
+        }
     }
 }
 
+// The files are sorted by path, so that the documentation doesn't depend on the order the file
+// system lists them in.
 pub fn find_cs_files(dir: &PathBuf) -> Vec<PathBuf> {
     let mut cs_files = Vec::new();
 
-    for entry in WalkDir::new(dir).into_iter().filter_map(Result::ok) {
-        if entry.path().extension().map_or(false, |ext| ext == "cs") {
+    for entry in WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(Result::ok)
+    {
+        if entry.path().extension().is_some_and(|ext| ext == "cs") {
             cs_files.push(entry.path().to_path_buf())
         }
     }
@@ -65,22 +443,15 @@ pub fn find_cs_files(dir: &PathBuf) -> Vec<PathBuf> {
     cs_files
 }
 
-pub fn extract_definition(line: &str, keyword: &str) -> Option<String> {
-    let pattern = format!(r"\b{}\s+(\w+)", keyword);
-    let re = Regex::new(&pattern).unwrap();
-
-    if let Some(captures) = re.captures(line) {
-        return Some(captures[1].to_string());
-    }
-    None
-}
-
-pub fn parse_cs_files(files: Vec<PathBuf>) -> Vec<ConstructInfo> {
+// `defines` are the preprocessor symbols to consider defined, e.g. `DEBUG`. Source spans are
+// made relative to `package_dir`.
+pub fn parse_cs_files(
+    package_dir: &Path,
+    files: Vec<PathBuf>,
+    defines: &[String],
+) -> Vec<ConstructInfo> {
     let mut constructs = Vec::new();
-    let mut seen_partial_classes = HashSet::new();
-    let mut inside_multiline_comment = false;
 
-    let mut extractor = DocstringExtractor::new();
     for file_path in files {
         let mut file_content = String::new();
         if let Ok(mut file) = File::open(&file_path) {
@@ -93,159 +464,1118 @@ pub fn parse_cs_files(files: Vec<PathBuf>) -> Vec<ConstructInfo> {
             continue;
         }
 
-        let mut current_docstring: Option<String> = None;
+        let relative_path = file_path
+            .strip_prefix(package_dir)
+            .unwrap_or(&file_path)
+            .to_path_buf();
+        for mut construct in parse_cs_source(&file_content, defines) {
+            if let Some(span) = &mut construct.span {
+                span.file = relative_path.clone();
+            }
+            construct.files.push(file_path.clone());
+            constructs.push(construct);
+        }
+    }
 
-        for line in file_content.lines() {
-            let line = line.trim();
+    let mut constructs = merge_partial_types(constructs);
+    classify_base_types(&mut constructs);
+    resolve_inheritdoc(&mut constructs);
+    constructs
+}
 
-            if let Some(doc_line) = extractor.extract_docstring(line) {
-                current_docstring = match current_docstring {
-                    Some(mut existing) => {
-                        existing.push(' ');
-                        existing.push_str(&doc_line);
-                        Some(existing)
-                    }
-                    None => Some(doc_line),
-                };
-            };
+// Combines the parts of each partial type into a single construct, keeping the position of the
+// first part.
+fn merge_partial_types(constructs: Vec<ConstructInfo>) -> Vec<ConstructInfo> {
+    let mut merged: Vec<ConstructInfo> = Vec::new();
+    let mut partial_indices: HashMap<(String, ConstructType), usize> = HashMap::new();
 
-            if skip_due_to_comment(line, &mut inside_multiline_comment) {
-                continue;
+    for construct in constructs {
+        if !construct.modifiers.is_partial {
+            merged.push(construct);
+            continue;
+        }
+        let key = (construct.full_name(), construct.construct_type.clone());
+        match partial_indices.get(&key) {
+            Some(&index) => merged[index].merge(construct),
+            None => {
+                partial_indices.insert(key, merged.len());
+                merged.push(construct);
             }
+        }
+    }
 
-            let access_modifier = extract_access_modifier(line);
-
-            for construct in ConstructType::iter() {
-                if let Some(name) = extract_definition(line, &construct.as_lowercase()) {
-                    if construct != ConstructType::Class
-                        || seen_partial_classes.insert(name.clone())
-                    {
-                        constructs.push(ConstructInfo {
-                            docstring: current_docstring.clone(),
-                            access_modifier,
-                            construct_type: construct,
-                            name,
-                        });
-                        current_docstring = None; // Reset the docstring after use
-                        break;
-                    }
+    merged
+}
+
+// Splits the base types of classes and records into the base class and the implemented
+// interfaces. Only the first base type can be a class; types that are not declared in the
+// sources are told apart by the `IName` naming convention.
+fn classify_base_types(constructs: &mut [ConstructInfo]) {
+    let declared: HashMap<String, ConstructType> = constructs
+        .iter()
+        .map(|c| (c.name.clone(), c.construct_type.clone()))
+        .collect();
+    for construct in constructs.iter_mut() {
+        let mut interfaces = construct.base_types.clone();
+        let can_inherit = matches!(
+            construct.construct_type,
+            ConstructType::Class | ConstructType::Record
+        );
+        if let Some(first) = interfaces.first().filter(|_| can_inherit) {
+            let name = strip_generic_arity(first);
+            let name = name.rsplit('.').next().unwrap_or_default();
+            let is_interface = match declared.get(name) {
+                Some(construct_type) => *construct_type == ConstructType::Interface,
+                None => {
+                    let mut chars = name.chars();
+                    chars.next() == Some('I') && chars.next().is_some_and(|c| c.is_uppercase())
                 }
+            };
+            if !is_interface {
+                construct.base_class = Some(interfaces.remove(0));
             }
         }
+        construct.interfaces = interfaces;
     }
-
-    constructs
 }
 
-pub fn extract_access_modifier(line: &str) -> AccessModifier {
-    let access_modifier_regex = Regex::new(&AccessModifier::variants_as_regex()).unwrap();
+pub fn parse_cs_source(source: &str, defines: &[String]) -> Vec<ConstructInfo> {
+    let mut parser = Parser::new(preprocess(tokenize(source), defines));
+    parser.parse_declarations(None);
+    parser.constructs
+}
 
-    if let Some(captures) = access_modifier_regex.captures(line) {
-        let modifier_str = captures.get(1).unwrap().as_str();
-        AccessModifier::from_str(modifier_str).unwrap_or(AccessModifier::Private)
-    } else {
-        AccessModifier::Private
+// Works out the accessibility from the modifiers of a declaration, including the compound
+// `protected internal` and `private protected` in either order. `default` applies when no access
+// modifier is given.
+pub fn extract_access_modifier(modifiers: &[String], default: AccessModifier) -> AccessModifier {
+    let has = |word: &str| modifiers.iter().any(|m| m == word);
+    match (has("protected"), has("internal"), has("private")) {
+        (true, true, _) => AccessModifier::ProtectedInternal,
+        (true, _, true) => AccessModifier::PrivateProtected,
+        _ => modifiers
+            .iter()
+            .find_map(|modifier| AccessModifier::from_str(modifier).ok())
+            .unwrap_or(default),
     }
 }
 
-struct DocstringExtractor {
-    in_summary: bool,
-    summary_content: String,
+const MODIFIERS: [&str; 20] = [
+    "public",
+    "private",
+    "protected",
+    "internal",
+    "abstract",
+    "sealed",
+    "static",
+    "readonly",
+    "partial",
+    "unsafe",
+    "virtual",
+    "override",
+    "extern",
+    "new",
+    "const",
+    "volatile",
+    "async",
+    "required",
+    "file",
+    "ref",
+];
+
+struct Parser {
+    tokens: Vec<Token>,
+    // Doc comment lines keyed by the index of the code token that follows them
+    docs: HashMap<usize, Vec<String>>,
+    pos: usize,
+    // Segments of the enclosing namespace, e.g. `["App", "Logging"]`
+    namespace: Vec<String>,
+    // `#region` names with the range of code tokens they cover
+    regions: Vec<(Range<usize>, String)>,
+    // Index of the first token of the declaration being parsed, including its attributes
+    declaration_start: usize,
+    constructs: Vec<ConstructInfo>,
 }
 
-impl DocstringExtractor {
-    fn new() -> Self {
+impl Parser {
+    fn new(all_tokens: Vec<Token>) -> Self {
+        let mut tokens = Vec::new();
+        let mut docs: HashMap<usize, Vec<String>> = HashMap::new();
+        let mut regions = Vec::new();
+        let mut open_regions = Vec::new();
+        for token in all_tokens {
+            match token.kind {
+                TokenKind::DocComment => docs.entry(tokens.len()).or_default().push(token.text),
+                TokenKind::Directive => {
+                    let directive = token.text.trim_start_matches('#').trim_start();
+                    if let Some(name) = directive.strip_prefix("region") {
+                        open_regions.push((tokens.len(), name.trim().to_string()));
+                    } else if directive.starts_with("endregion") {
+                        if let Some((start, name)) = open_regions.pop() {
+                            regions.push((start..tokens.len(), name));
+                        }
+                    }
+                }
+                _ => tokens.push(token),
+            }
+        }
+        for (start, name) in open_regions {
+            regions.push((start..tokens.len(), name));
+        }
         Self {
-            in_summary: false,
-            summary_content: String::new(),
+            tokens,
+            docs,
+            pos: 0,
+            namespace: Vec::new(),
+            regions,
+            declaration_start: 0,
+            constructs: Vec::new(),
+        }
+    }
+
+    fn peek(&self, ahead: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + ahead)
+    }
+
+    fn peek_is(&self, ahead: usize, text: &str) -> bool {
+        self.peek(ahead).is_some_and(|t| t.is(text))
+    }
+
+    fn bump_n(&mut self, n: usize) {
+        self.pos += n;
+    }
+
+    fn bump(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    // Parses declarations until the `}` closing the current block (which is consumed) or the end of input.
+    // `container` is the index of the construct whose body is being parsed, if any.
+    fn parse_declarations(&mut self, container: Option<usize>) {
+        loop {
+            let doc = self
+                .docs
+                .get(&self.pos)
+                .and_then(|lines| parse_doc_comment(lines));
+            self.declaration_start = self.pos;
+            let attributes = self.take_attributes();
+            let modifiers = self.take_modifiers();
+
+            let Some(token) = self.peek(0) else {
+                return;
+            };
+            if token.is("}") {
+                self.bump();
+                return;
+            }
+            if token.is("namespace") {
+                self.parse_namespace();
+                continue;
+            }
+            if token.is("delegate") {
+                self.parse_delegate(container, modifiers, attributes, doc);
+                continue;
+            }
+            match self.construct_keyword() {
+                Some((construct_type, keyword_len))
+                    if self.peek(keyword_len).is_some_and(Token::is_identifier) =>
+                {
+                    self.bump_n(keyword_len);
+                    self.parse_type(container, construct_type, modifiers, attributes, doc)
+                }
+                _ => match container {
+                    Some(container) => self.parse_member(container, modifiers, attributes, doc),
+                    None => self.skip_member(),
+                },
+            }
         }
     }
 
-    fn extract_docstring(&mut self, line: &str) -> Option<String> {
-        let docstring_regex = Regex::new(r"^\s*///\s*(.*)$").unwrap();
-        let xml_tag_regex = Regex::new(r"</?[^>]+>").unwrap();
+    // Recognizes the keyword(s) introducing a type declaration, returning the construct type and
+    // the number of keyword tokens.
+    fn construct_keyword(&self) -> Option<(ConstructType, usize)> {
+        let token = self.peek(0)?;
+        if token.is("record") {
+            return Some(match self.peek(1) {
+                Some(next) if next.is("struct") => (ConstructType::RecordStruct, 2),
+                Some(next) if next.is("class") => (ConstructType::Record, 2),
+                _ => (ConstructType::Record, 1),
+            });
+        }
+        [
+            ConstructType::Class,
+            ConstructType::Struct,
+            ConstructType::Enum,
+            ConstructType::Interface,
+        ]
+        .into_iter()
+        .find(|c| token.is(&c.as_lowercase()))
+        .map(|c| (c, 1))
+    }
 
-        if let Some(captures) = docstring_regex.captures(line) {
-            let doc_line = captures.get(1).unwrap().as_str();
+    // Handles both `namespace A.B { ... }` and the file-scoped `namespace A.B;`, which applies to
+    // the rest of the file.
+    fn parse_namespace(&mut self) {
+        self.bump();
+        let start = self.pos;
+        while self.peek(0).is_some_and(|t| !t.is("{") && !t.is(";")) {
+            self.bump();
+        }
+        let name = join_tokens(&self.tokens[start..self.pos]);
+        self.namespace.push(name);
+        if self.bump().is_some_and(|t| t.is("{")) {
+            self.parse_declarations(None);
+            self.namespace.pop();
+        }
+    }
 
-            let opening_tag = "<summary>";
-            let closing_tag = "</summary>";
+    // Types declared directly in a namespace are internal unless stated otherwise, everything
+    // declared inside a type is private, except inside interfaces where it is public.
+    fn default_access(&self, container: Option<usize>) -> AccessModifier {
+        match container.map(|c| &self.constructs[c].construct_type) {
+            None => AccessModifier::Internal,
+            Some(ConstructType::Interface) => AccessModifier::Public,
+            Some(_) => AccessModifier::Private,
+        }
+    }
+
+    // The name of the innermost `#region` around the cursor.
+    fn current_region(&self) -> Option<String> {
+        self.regions
+            .iter()
+            .filter(|(range, _)| range.contains(&self.pos))
+            .max_by_key(|(range, _)| range.start)
+            .map(|(_, name)| name.clone())
+    }
+
+    fn current_namespace(&self) -> Option<String> {
+        (!self.namespace.is_empty()).then(|| self.namespace.join("."))
+    }
+
+    fn parse_type(
+        &mut self,
+        container: Option<usize>,
+        construct_type: ConstructType,
+        modifiers: Vec<String>,
+        attributes: Vec<Attribute>,
+        doc: Option<DocComment>,
+    ) {
+        let start = self.declaration_start;
+        let header = self.take_type_header();
+        let mut construct = ConstructInfo {
+            doc,
+            access_modifier: extract_access_modifier(&modifiers, self.default_access(container)),
+            namespace: self.current_namespace(),
+            type_parameters: parse_type_parameters(&header, 1),
+            constraints: parse_constraints(&header),
+            base_types: parse_base_types(&header),
+            modifiers: Modifiers::from_keywords(&modifiers),
+            attributes,
+            region: self.current_region(),
+            ..ConstructInfo::new(construct_type.clone(), &header[0].text)
+        };
+        self.set_parent(&mut construct, container);
+        if construct_type == ConstructType::Enum {
+            construct.underlying_type = construct.base_types.pop();
+        }
 
-            let has_opening_tag = doc_line.contains(opening_tag);
-            let has_closing_tag = doc_line.contains(closing_tag);
+        // Primary constructor parameters, e.g. `record Point(int X, int Y)`
+        let mut after_name = 1;
+        if header.get(1).is_some_and(|t| t.is("<")) {
+            after_name += group_contents(&header, 1).len() + 2;
+        }
+        if header.get(after_name).is_some_and(|t| t.is("(")) {
+            construct.parameters = parse_parameters(group_contents(&header, after_name));
+        }
+        if matches!(
+            construct_type,
+            ConstructType::Record | ConstructType::RecordStruct
+        ) {
+            let is_mutable = construct_type == ConstructType::RecordStruct
+                && !modifiers.iter().any(|m| m == "readonly");
+            let accessors = if is_mutable { "get; set" } else { "get; init" };
+            for parameter in &construct.parameters {
+                // Positional properties are documented by the record's `<param>` tags
+                let doc = construct.doc.as_ref().and_then(|doc| {
+                    doc.param(&parameter.name).map(|param| DocComment {
+                        summary: Some(param.text.clone()),
+                        ..DocComment::default()
+                    })
+                });
+                construct.members.push(MemberInfo {
+                    doc,
+                    access_modifier: AccessModifier::Public,
+                    member_type: MemberType::Property,
+                    name: parameter.name.clone(),
+                    type_name: Some(parameter.type_name.clone()),
+                    parameters: Vec::new(),
+                    modifiers: Modifiers::default(),
+                    attributes: Vec::new(),
+                    region: construct.region.clone(),
+                    accessors: accessors.split("; ").map(String::from).collect(),
+                    signature: format!(
+                        "public {} {} {{ {}; }}",
+                        parameter.type_name, parameter.name, accessors
+                    ),
+                });
+            }
+        }
 
-            if has_opening_tag && has_closing_tag {
-                if let Some(start) = doc_line.find(opening_tag) {
-                    if let Some(end) = doc_line.find(closing_tag) {
-                        self.summary_content.push_str(&doc_line[start + 9..end]);
-                        // Clean the accumulated summary content and return it
-                        let cleaned_text = xml_tag_regex
-                            .replace_all(&self.summary_content, "")
-                            .to_string();
-                        self.summary_content.clear(); // Clear the content for the next block
-                        return Some(cleaned_text.trim().to_string());
+        self.constructs.push(construct);
+        let index = self.constructs.len() - 1;
+        if self.peek_is(0, "{") {
+            if construct_type == ConstructType::Enum {
+                self.bump();
+                let members = self.parse_enum_members();
+                self.constructs[index].enum_members = members;
+            } else {
+                self.bump();
+                self.parse_declarations(Some(index));
+            }
+        }
+        if self.peek_is(0, ";") {
+            self.bump();
+        }
+        self.constructs[index].span = Some(self.span_from(start));
+    }
+
+    // Parses the members of an enum body up to and including its closing `}`.
+    fn parse_enum_members(&mut self) -> Vec<EnumMember> {
+        let mut members = Vec::new();
+        loop {
+            let doc = self
+                .docs
+                .get(&self.pos)
+                .and_then(|lines| parse_doc_comment(lines));
+            let attributes = self.take_attributes();
+            let Some(token) = self.bump() else {
+                break;
+            };
+            if token.is("}") {
+                break;
+            }
+            if !token.is_identifier() {
+                continue;
+            }
+            let name = token.text.clone();
+            let mut value = None;
+            if self.peek_is(0, "=") {
+                self.bump();
+                let mut expression = Vec::new();
+                while let Some(token) = self.peek(0) {
+                    if token.is(",") || token.is("}") {
+                        break;
+                    }
+                    if token.is("(") {
+                        let start = self.pos;
+                        self.skip_balanced();
+                        expression.extend_from_slice(&self.tokens[start..self.pos]);
+                    } else {
+                        expression.push(token.clone());
+                        self.bump();
                     }
                 }
-            } else if has_opening_tag {
-                self.in_summary = true;
-                if let Some(start) = doc_line.find(opening_tag) {
-                    self.summary_content.push_str(&doc_line[start + 9..]); // Append text after <summary>
+                value = Some(join_tokens(&expression));
+            }
+            if self.peek_is(0, ",") {
+                self.bump();
+            }
+            members.push(EnumMember {
+                doc,
+                attributes,
+                name,
+                value,
+            });
+        }
+        members
+    }
+
+    fn parse_delegate(
+        &mut self,
+        container: Option<usize>,
+        modifiers: Vec<String>,
+        attributes: Vec<Attribute>,
+        doc: Option<DocComment>,
+    ) {
+        let start = self.declaration_start;
+        self.bump();
+        let header = self.take_member_header();
+        let template = MemberInfo {
+            doc: None,
+            access_modifier: AccessModifier::Public,
+            member_type: MemberType::Method,
+            name: String::new(),
+            type_name: None,
+            parameters: Vec::new(),
+            modifiers: Modifiers::default(),
+            attributes: Vec::new(),
+            region: None,
+            accessors: Vec::new(),
+            signature: String::new(),
+        };
+        if let Some(signature) = classify_member(template, &header, "") {
+            let type_parameters = match find_top_level(&header, "(") {
+                Some(open) if open > 0 && header[open - 1].is(">") => header[..open]
+                    .iter()
+                    .rposition(|t| t.is("<"))
+                    .map(|lt| parse_type_parameters(&header, lt))
+                    .unwrap_or_default(),
+                _ => Vec::new(),
+            };
+            let mut construct = ConstructInfo {
+                doc,
+                access_modifier: extract_access_modifier(
+                    &modifiers,
+                    self.default_access(container),
+                ),
+                parameters: signature.parameters,
+                return_type: signature.type_name,
+                type_parameters,
+                constraints: parse_constraints(&header),
+                modifiers: Modifiers::from_keywords(&modifiers),
+                attributes,
+                region: self.current_region(),
+                namespace: self.current_namespace(),
+                ..ConstructInfo::new(ConstructType::Delegate, &signature.name)
+            };
+            self.set_parent(&mut construct, container);
+            self.constructs.push(construct);
+            self.skip_member();
+            let index = self.constructs.len() - 1;
+            self.constructs[index].span = Some(self.span_from(start));
+        } else {
+            self.skip_member();
+        }
+    }
+
+    // The span from the token at `start` to the last consumed token. The file is filled in by
+    // `parse_cs_files`.
+    fn span_from(&self, start: usize) -> SourceSpan {
+        let start = self.tokens[start].span.start;
+        let end = self.tokens[self.pos.saturating_sub(1)].span.end;
+        SourceSpan {
+            file: PathBuf::new(),
+            start_line: start.line,
+            start_column: start.column,
+            end_line: end.line,
+            end_column: end.column,
+        }
+    }
+
+    fn set_parent(&mut self, construct: &mut ConstructInfo, container: Option<usize>) {
+        if let Some(container) = container {
+            let parent = &mut self.constructs[container];
+            parent.children.push(construct.name.clone());
+            construct.parent = Some(parent.qualified_name());
+        }
+    }
+
+    // Collects a type declaration header (name, generic parameters, primary constructor, base
+    // types and constraints) up to the type body or terminating `;`.
+    fn take_type_header(&mut self) -> Vec<Token> {
+        let mut header = Vec::new();
+        let mut depth = 0;
+        while let Some(token) = self.peek(0) {
+            if depth == 0 && (token.is("{") || token.is(";") || token.is("}")) {
+                break;
+            }
+            if token.kind == TokenKind::Punctuation {
+                match token.text.as_str() {
+                    "(" | "[" => depth += 1,
+                    ")" | "]" => depth -= 1,
+                    _ => {}
                 }
-            } else if has_closing_tag {
-                if let Some(end) = doc_line.find(closing_tag) {
-                    self.summary_content.push_str(&doc_line[..end]); // Append text before </summary>
+            }
+            header.push(token.clone());
+            self.bump();
+        }
+        header
+    }
+
+    fn parse_member(
+        &mut self,
+        container: usize,
+        modifiers: Vec<String>,
+        attributes: Vec<Attribute>,
+        doc: Option<DocComment>,
+    ) {
+        let header = self.take_member_header();
+        let is_event = header.first().is_some_and(|t| t.is("event"));
+        let declaration = if is_event { &header[1..] } else { &header[..] };
+        let prefix: Vec<&str> = modifiers
+            .iter()
+            .map(String::as_str)
+            .chain(is_event.then_some("event"))
+            .collect();
+        let member = MemberInfo {
+            doc,
+            access_modifier: extract_access_modifier(
+                &modifiers,
+                self.default_access(Some(container)),
+            ),
+            member_type: MemberType::Field,
+            name: String::new(),
+            type_name: None,
+            parameters: Vec::new(),
+            modifiers: Modifiers::from_keywords(&modifiers),
+            attributes,
+            region: self.current_region(),
+            accessors: Vec::new(),
+            signature: String::new(),
+        };
+
+        let container_name = self.constructs[container].name.clone();
+        let Some(mut member) = classify_member(member, declaration, &container_name) else {
+            self.skip_member();
+            return;
+        };
+        if is_event {
+            member.member_type = MemberType::Event;
+        } else if member.member_type == MemberType::Property
+            && !self.peek_is(0, "{")
+            && !self.peek_is(0, "=>")
+        {
+            member.member_type = MemberType::Field;
+        }
+        member.signature = prefix
+            .iter()
+            .map(|s| s.to_string())
+            .chain(std::iter::once(member.signature))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        match member.member_type {
+            MemberType::Field | MemberType::Event
+                if !self.peek_is(0, "{") && !self.peek_is(0, "=>") =>
+            {
+                self.parse_declarators(container, member)
+            }
+            MemberType::Property | MemberType::Indexer | MemberType::Event => {
+                if self.peek_is(0, "{") {
+                    member.accessors = self.take_accessors();
+                    if self.peek_is(0, "=") {
+                        self.skip_member();
+                    }
+                } else {
+                    member.accessors = vec!["get".to_string()];
+                    self.skip_member();
+                }
+                if !member.accessors.is_empty() {
+                    member.signature = format!(
+                        "{} {{ {}; }}",
+                        member.signature,
+                        member.accessors.join("; ")
+                    );
                 }
-                self.in_summary = false;
-
-                // Clean the accumulated summary content and return it
-                let cleaned_text = xml_tag_regex
-                    .replace_all(&self.summary_content, "")
-                    .to_string();
-                self.summary_content.clear(); // Clear the content for the next block
-                return Some(cleaned_text.trim().to_string());
-            } else if self.in_summary {
-                if !self.summary_content.is_empty() {
-                    self.summary_content.push(' ');
+                self.constructs[container].members.push(member);
+            }
+            _ => {
+                self.skip_member();
+                self.constructs[container].members.push(member);
+            }
+        }
+    }
+
+    // Collects the tokens of a member declaration up to its body, initializer or the separator of
+    // the next declarator.
+    fn take_member_header(&mut self) -> Vec<Token> {
+        let mut header = Vec::new();
+        let mut depth = 0;
+        let mut angle_depth = 0;
+        while let Some(token) = self.peek(0) {
+            let at_top = depth == 0 && angle_depth == 0;
+            if at_top && ["{", "}", ";", "=>", "=", ","].iter().any(|t| token.is(t)) {
+                break;
+            }
+            if token.is("operator") {
+                // Operator symbols such as `<` or `>=` must not be mistaken for generic brackets
+                while self
+                    .peek(0)
+                    .is_some_and(|t| !t.is("(") && !t.is(";") && !t.is("{"))
+                {
+                    header.push(self.bump().unwrap().clone());
                 }
-                self.summary_content.push_str(doc_line);
+                continue;
             }
+            match token.text.as_str() {
+                "(" | "[" if token.kind == TokenKind::Punctuation => depth += 1,
+                ")" | "]" if token.kind == TokenKind::Punctuation => depth -= 1,
+                "<" if token.kind == TokenKind::Punctuation => angle_depth += 1,
+                ">" if token.kind == TokenKind::Punctuation && angle_depth > 0 => angle_depth -= 1,
+                _ => {}
+            }
+            header.push(token.clone());
+            self.bump();
         }
+        header
+    }
 
-        None
+    // Parses `{ get; private set; }` style accessor lists, returning the accessor declarations.
+    fn take_accessors(&mut self) -> Vec<String> {
+        let mut accessors = Vec::new();
+        self.bump();
+        while let Some(token) = self.peek(0) {
+            if token.is("}") {
+                self.bump();
+                break;
+            }
+            if token.is("[") {
+                self.skip_attributes();
+                continue;
+            }
+            let modifiers = self.take_modifiers();
+            match self.peek(0) {
+                Some(token) if token.is_identifier() => {
+                    let mut accessor = modifiers;
+                    accessor.push(token.text.clone());
+                    accessors.push(accessor.join(" "));
+                    self.bump();
+                    if self.peek_is(0, ";") {
+                        self.bump();
+                    } else {
+                        self.skip_member();
+                    }
+                }
+                Some(_) => {
+                    self.bump();
+                }
+                None => break,
+            }
+        }
+        accessors
+    }
+
+    // Parses the remaining declarators of a field or event declaration such as `int a = 1, b;`.
+    fn parse_declarators(&mut self, container: usize, first: MemberInfo) {
+        let mut declarators = vec![first];
+        loop {
+            if self.peek_is(0, "=") {
+                self.bump();
+                self.skip_initializer();
+            }
+            if self.peek_is(0, ",") && self.peek(1).is_some_and(Token::is_identifier) {
+                self.bump();
+                let name = self.bump().unwrap().text.clone();
+                let previous = declarators.last().unwrap();
+                let type_name = previous.type_name.clone().unwrap_or_default();
+                let signature = previous
+                    .signature
+                    .strip_suffix(&previous.name)
+                    .unwrap_or(&previous.signature)
+                    .to_string()
+                    + &name;
+                declarators.push(MemberInfo {
+                    doc: previous.doc.clone(),
+                    access_modifier: previous.access_modifier,
+                    member_type: previous.member_type,
+                    name,
+                    type_name: Some(type_name),
+                    parameters: Vec::new(),
+                    modifiers: previous.modifiers,
+                    attributes: previous.attributes.clone(),
+                    region: previous.region.clone(),
+                    accessors: Vec::new(),
+                    signature,
+                });
+                continue;
+            }
+            if self.peek_is(0, ";") {
+                self.bump();
+            }
+            break;
+        }
+        self.constructs[container].members.extend(declarators);
     }
-}
 
-pub fn skip_due_to_comment(line: &str, inside_multiline_comment: &mut bool) -> bool {
-    let mut result = false;
+    // Skips an initializer expression up to the `,` that starts another declarator or the final `;`.
+    fn skip_initializer(&mut self) {
+        while let Some(token) = self.peek(0) {
+            if token.is(";") || token.is("}") {
+                return;
+            }
+            if token.is(",") {
+                let starts_declarator = self.peek(1).is_some_and(Token::is_identifier)
+                    && ["=", ",", ";"].iter().any(|t| self.peek_is(2, t));
+                if starts_declarator {
+                    return;
+                }
+                self.bump();
+            } else if token.is("(") || token.is("[") || token.is("{") {
+                self.skip_balanced();
+            } else {
+                self.bump();
+            }
+        }
+    }
 
-    if line.contains("/*") {
-        *inside_multiline_comment = true;
-        result = true;
+    fn skip_attributes(&mut self) {
+        while self.peek_is(0, "[") {
+            self.skip_balanced();
+        }
     }
 
-    if *inside_multiline_comment {
-        if line.contains("*/") {
-            *inside_multiline_comment = false;
+    // Parses the attribute lists at the cursor, e.g. `[Serializable, Obsolete("Old")]`.
+    // Assembly and module attributes don't belong to the following declaration and are dropped.
+    fn take_attributes(&mut self) -> Vec<Attribute> {
+        let mut attributes = Vec::new();
+        while self.peek_is(0, "[") {
+            let start = self.pos;
+            self.skip_balanced();
+            let mut list = group_contents(&self.tokens[start..self.pos], 0);
+            let mut target = None;
+            if list.get(1).is_some_and(|t| t.is(":")) {
+                target = Some(list[0].text.clone());
+                list = &list[2..];
+            }
+            if matches!(target.as_deref(), Some("assembly" | "module")) {
+                continue;
+            }
+            for attribute in split_top_level(list, ",") {
+                let (name, arguments) = match find_top_level(attribute, "(") {
+                    Some(open) => (
+                        &attribute[..open],
+                        split_top_level(group_contents(attribute, open), ",")
+                            .into_iter()
+                            .map(join_tokens)
+                            .collect(),
+                    ),
+                    None => (attribute, Vec::new()),
+                };
+                attributes.push(Attribute {
+                    name: join_tokens(name),
+                    arguments,
+                    target: target.clone(),
+                });
+            }
         }
-        result = true;
+        attributes
     }
 
-    // Skip single-line and XML documentation comments
-    if line.starts_with("//") || line.starts_with("///") {
-        result = true;
+    fn take_modifiers(&mut self) -> Vec<String> {
+        let mut modifiers = Vec::new();
+        while let Some(token) = self.peek(0) {
+            // `new(` is an expression rather than a modifier, while other modifiers can be
+            // followed by a tuple type, e.g. `public (int, string) GetPair()`
+            if token.is_identifier()
+                && MODIFIERS.contains(&token.text.as_str())
+                && !(token.is("new") && self.peek_is(1, "("))
+            {
+                modifiers.push(token.text.clone());
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        modifiers
+    }
+
+    // Skips a bracketed group starting at the cursor, including any nested groups.
+    fn skip_balanced(&mut self) {
+        let mut depth = 0;
+        while let Some(token) = self.bump() {
+            if token.kind != TokenKind::Punctuation {
+                continue;
+            }
+            match token.text.as_str() {
+                "(" | "[" | "{" => depth += 1,
+                ")" | "]" | "}" => depth -= 1,
+                _ => {}
+            }
+            if depth <= 0 {
+                return;
+            }
+        }
     }
 
-    // Skip lines with comments before keywords for all construct types
-    if line.contains("//") {
-        for construct in ConstructType::iter() {
-            if let Some(keyword_pos) = line.find(&construct.as_lowercase()) {
-                if line.find("//").unwrap() < keyword_pos {
-                    return true;
+    // Skips a member declaration along with its body, initializer or expression body.
+    fn skip_member(&mut self) {
+        let mut in_expression = false;
+        while let Some(token) = self.peek(0) {
+            if token.is(";") {
+                self.bump();
+                return;
+            }
+            if token.is("}") {
+                if !in_expression {
+                    return;
+                }
+                self.bump();
+            } else if token.is("=") || token.is("=>") {
+                in_expression = true;
+                self.bump();
+            } else if token.is("{") && !in_expression {
+                self.skip_balanced();
+                if !self.peek_is(0, "=") {
+                    return;
                 }
+            } else if token.is("(") || token.is("[") || token.is("{") {
+                self.skip_balanced();
+            } else {
+                self.bump();
+            }
+        }
+    }
+}
+
+// Works out what kind of member a declaration header describes and fills in its name, type,
+// parameters and signature. Returns `None` for declarations that are not documented, such as
+// finalizers.
+fn classify_member(
+    mut member: MemberInfo,
+    header: &[Token],
+    container_name: &str,
+) -> Option<MemberInfo> {
+    if header.is_empty() || header[0].is("~") {
+        return None;
+    }
+
+    if let Some(operator) = header.iter().position(|t| t.is("operator")) {
+        let open = operator + header[operator..].iter().position(|t| t.is("("))?;
+        let symbol = join_tokens(&header[operator + 1..open]);
+        let is_conversion = operator > 0
+            && (header[operator - 1].is("implicit") || header[operator - 1].is("explicit"));
+        if is_conversion {
+            member.name = format!("{} operator {}", header[operator - 1].text, symbol);
+            member.type_name = Some(symbol);
+        } else {
+            member.name = format!("operator {}", symbol.replace(' ', ""));
+            member.type_name = Some(join_tokens(&header[..operator]));
+        }
+        member.member_type = MemberType::Operator;
+        member.parameters = parse_parameters(group_contents(header, open));
+        member.signature = join_tokens(header);
+        return Some(member);
+    }
+
+    if let Some(this) = find_top_level(header, "this") {
+        if header.get(this + 1).is_some_and(|t| t.is("[")) {
+            member.member_type = MemberType::Indexer;
+            member.name = "this".to_string();
+            member.type_name = Some(join_tokens(&header[..qualified_name_start(header, this)]));
+            member.parameters = parse_parameters(group_contents(header, this + 1));
+            member.signature = join_tokens(header);
+            return Some(member);
+        }
+    }
+
+    if let Some(open) = find_parameter_list(header) {
+        let mut name_end = open;
+        if open > 0 && header[open - 1].is(">") {
+            name_end = header[..open].iter().rposition(|t| t.is("<"))?;
+        }
+        let name_index = name_end.checked_sub(1)?;
+        if !header[name_index].is_identifier() {
+            return None;
+        }
+        let name_start = qualified_name_start(header, name_index);
+        let close = open + group_contents(header, open).len() + 1;
+        member.name = header[name_index].text.clone();
+        member.parameters = parse_parameters(group_contents(header, open));
+        if name_start == 0 && member.name == container_name {
+            member.member_type = MemberType::Constructor;
+            member.signature = join_tokens(&header[..=close.min(header.len() - 1)]);
+        } else {
+            member.member_type = MemberType::Method;
+            member.type_name = Some(join_tokens(&header[..name_start]));
+            member.signature = join_tokens(header);
+        }
+        return Some(member);
+    }
+
+    let name_index = header.len() - 1;
+    if !header[name_index].is_identifier() || name_index == 0 {
+        return None;
+    }
+    let name_start = qualified_name_start(header, name_index);
+    member.member_type = MemberType::Property;
+    member.name = header[name_index].text.clone();
+    member.type_name = Some(join_tokens(&header[..name_start]));
+    member.signature = join_tokens(header);
+    Some(member)
+}
+
+// The `(` opening the parameter list of a method or constructor, skipping tuple types such as
+// `(int, string)` in `(int, string) GetPair()`.
+fn find_parameter_list(header: &[Token]) -> Option<usize> {
+    let mut start = 0;
+    while let Some(i) = find_top_level(&header[start..], "(") {
+        let open = start + i;
+        if open > 0 && (header[open - 1].is_identifier() || header[open - 1].is(">")) {
+            return Some(open);
+        }
+        start = open + group_contents(header, open).len() + 2;
+        if start >= header.len() {
+            return None;
+        }
+    }
+    None
+}
+
+// Extracts the generic parameter list if the token at `open` starts one, e.g. `<in T, [A] U>`.
+fn parse_type_parameters(header: &[Token], open: usize) -> Vec<String> {
+    if !header.get(open).is_some_and(|t| t.is("<")) {
+        return Vec::new();
+    }
+    split_top_level(group_contents(header, open), ",")
+        .into_iter()
+        .map(|mut parameter| {
+            while parameter.first().is_some_and(|t| t.is("[")) {
+                parameter = &parameter[group_contents(parameter, 0).len() + 2..];
+            }
+            join_tokens(parameter)
+        })
+        .collect()
+}
+
+// Extracts the `where` clauses of a declaration header.
+fn parse_constraints(header: &[Token]) -> Vec<TypeConstraint> {
+    let Some(first) = find_top_level(header, "where") else {
+        return Vec::new();
+    };
+    split_top_level(&header[first + 1..], "where")
+        .into_iter()
+        .filter_map(|clause| {
+            let colon = find_top_level(clause, ":")?;
+            Some(TypeConstraint {
+                type_parameter: join_tokens(&clause[..colon]),
+                constraints: split_top_level(&clause[colon + 1..], ",")
+                    .into_iter()
+                    .map(join_tokens)
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+// Extracts the base class and interface list following the `:` of a type header.
+fn parse_base_types(header: &[Token]) -> Vec<String> {
+    let header = match find_top_level(header, "where") {
+        Some(constraints) => &header[..constraints],
+        None => header,
+    };
+    let Some(colon) = find_top_level(header, ":") else {
+        return Vec::new();
+    };
+    split_top_level(&header[colon + 1..], ",")
+        .into_iter()
+        .map(|base_type| match find_top_level(base_type, "(") {
+            // Record base types may pass arguments to the base constructor
+            Some(arguments) => join_tokens(&base_type[..arguments]),
+            None => join_tokens(base_type),
+        })
+        .collect()
+}
+
+// Extends a member name backwards over an explicit interface qualifier such as `IDisposable.`.
+fn qualified_name_start(header: &[Token], mut name_index: usize) -> usize {
+    while name_index >= 2
+        && header[name_index - 1].is(".")
+        && header[name_index - 2].is_identifier()
+    {
+        name_index -= 2;
+    }
+    name_index
+}
+
+// Returns the tokens between the bracket at `open` and its matching closing bracket. Angle
+// brackets are only matched when `open` is itself a `<`.
+fn group_contents(tokens: &[Token], open: usize) -> &[Token] {
+    let angles = tokens[open].is("<");
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        if token.kind != TokenKind::Punctuation {
+            continue;
+        }
+        match token.text.as_str() {
+            "(" | "[" | "{" => depth += 1,
+            ")" | "]" | "}" => depth -= 1,
+            "<" if angles => depth += 1,
+            ">" if angles => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 {
+            return &tokens[open + 1..i];
+        }
+    }
+    &tokens[(open + 1).min(tokens.len())..]
+}
+
+// Finds the first occurrence of `text` outside of any brackets, including generic `<...>` lists.
+fn find_top_level(tokens: &[Token], text: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        if depth == 0 && token.is(text) {
+            return Some(i);
+        }
+        if token.kind == TokenKind::Punctuation {
+            match token.text.as_str() {
+                "(" | "[" | "{" | "<" => depth += 1,
+                ")" | "]" | "}" | ">" => depth -= 1,
+                _ => {}
             }
         }
     }
-    result
+    None
+}
+
+// Splits tokens on top-level occurrences of `separator`.
+fn split_top_level<'t>(tokens: &'t [Token], separator: &str) -> Vec<&'t [Token]> {
+    let mut parts = Vec::new();
+    let mut rest = tokens;
+    while let Some(i) = find_top_level(rest, separator) {
+        parts.push(&rest[..i]);
+        rest = &rest[i + 1..];
+    }
+    if !rest.is_empty() {
+        parts.push(rest);
+    }
+    parts
+}
+
+const PARAMETER_MODIFIERS: [&str; 7] = ["this", "ref", "out", "in", "params", "scoped", "readonly"];
+
+fn parse_parameters(tokens: &[Token]) -> Vec<Parameter> {
+    let mut parameters = Vec::new();
+    for mut part in split_top_level(tokens, ",") {
+        while part.first().is_some_and(|t| t.is("[")) {
+            part = &part[group_contents(part, 0).len() + 2..];
+        }
+        let modifier_count = part
+            .iter()
+            .take_while(|t| PARAMETER_MODIFIERS.contains(&t.text.as_str()))
+            .count();
+        let (declaration, default_value) = match find_top_level(part, "=") {
+            Some(eq) => (
+                &part[modifier_count..eq],
+                Some(join_tokens(&part[eq + 1..])),
+            ),
+            None => (&part[modifier_count..], None),
+        };
+        let Some((name, type_tokens)) = declaration.split_last() else {
+            continue;
+        };
+        parameters.push(Parameter {
+            name: name.text.clone(),
+            type_name: join_tokens(type_tokens),
+            modifier: (modifier_count > 0).then(|| join_tokens(&part[..modifier_count])),
+            default_value,
+        });
+    }
+    parameters
+}
+
+// Joins tokens back into source text, keeping a single space wherever the source had whitespace.
+pub fn join_tokens(tokens: &[Token]) -> String {
+    let mut text = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 && tokens[i - 1].span.end.offset < token.span.start.offset {
+            text.push(' ');
+        }
+        text.push_str(&token.text);
+    }
+    text
 }
 
 #[cfg(test)]
@@ -253,6 +1583,13 @@ mod tests {
     use std::fs;
 
     use super::*;
+    use crate::doc_comment::plain_text;
+
+    fn summary_of(doc: &Option<DocComment>) -> Option<String> {
+        doc.as_ref()
+            .and_then(|doc| doc.summary.as_deref())
+            .map(plain_text)
+    }
 
     #[test]
     fn test_construct_type_as_lowercase() {
@@ -260,23 +1597,31 @@ mod tests {
         assert_eq!(ConstructType::Struct.as_lowercase(), "struct");
         assert_eq!(ConstructType::Enum.as_lowercase(), "enum");
         assert_eq!(ConstructType::Interface.as_lowercase(), "interface");
-    }
-
-    #[test]
-    fn test_access_modifier_variants_as_regex() {
-        let regex_pattern = AccessModifier::variants_as_regex();
-        assert!(regex::Regex::new(&regex_pattern).is_ok());
+        assert_eq!(ConstructType::RecordStruct.as_lowercase(), "record_struct");
+        assert_eq!(
+            ConstructType::Delegate.as_placeholder("_name"),
+            "[delegate_name]"
+        );
     }
 
     #[test]
     fn test_find_cs_files() {
         let test_dir = PathBuf::from("_find_cs_test_data");
         fs::create_dir_all(&test_dir).unwrap();
-        File::create(test_dir.join("example1.cs")).unwrap();
+        fs::create_dir_all(test_dir.join("b")).unwrap();
         File::create(test_dir.join("example2.cs")).unwrap();
+        File::create(test_dir.join("b").join("example.cs")).unwrap();
+        File::create(test_dir.join("example1.cs")).unwrap();
 
         let cs_files = find_cs_files(&test_dir);
-        assert_eq!(cs_files.len(), 2);
+        assert_eq!(
+            cs_files,
+            vec![
+                test_dir.join("b").join("example.cs"),
+                test_dir.join("example1.cs"),
+                test_dir.join("example2.cs"),
+            ]
+        );
         fs::remove_dir_all(&test_dir).unwrap();
     }
 
@@ -287,140 +1632,663 @@ mod tests {
         fs::write(test_dir.join("example.cs"), "public class MyClass { }").unwrap();
 
         let cs_files = find_cs_files(&test_dir);
-        let constructs = parse_cs_files(cs_files);
+        let constructs = parse_cs_files(&test_dir, cs_files, &[]);
         assert_eq!(constructs.len(), 1);
+        let span = constructs[0].span.as_ref().unwrap();
+        assert_eq!(span.file, PathBuf::from("example.cs"));
+        assert_eq!(span.start_line, 1);
         assert_eq!(constructs[0].name, "MyClass");
         assert_eq!(constructs[0].construct_type, ConstructType::Class);
         fs::remove_dir_all(&test_dir).unwrap();
     }
 
     #[test]
-    fn test_extract_definition() {
-        let line = "public class MyClass";
-        let keyword = "class";
-        let result = extract_definition(line, keyword);
-        assert_eq!(result, Some("MyClass".to_string()))
+    fn test_parse_multi_line_declaration() {
+        let constructs = parse_cs_source("public\n    class\n    MyClass\n{\n}", &[]);
+        assert_eq!(constructs.len(), 1);
+        assert_eq!(constructs[0].name, "MyClass");
+        assert_eq!(constructs[0].access_modifier, AccessModifier::Public);
     }
 
     #[test]
     fn test_extract_access_modifier() {
+        let words = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+        let extract = |s: &str| extract_access_modifier(&words(s), AccessModifier::Private);
+        assert_eq!(extract("public static"), AccessModifier::Public);
+        assert_eq!(extract("private"), AccessModifier::Private);
+        assert_eq!(extract("sealed protected"), AccessModifier::Protected);
+        assert_eq!(extract("internal"), AccessModifier::Internal);
         assert_eq!(
-            extract_access_modifier("public class MyClass"),
-            AccessModifier::Public
+            extract("protected internal"),
+            AccessModifier::ProtectedInternal
         );
         assert_eq!(
-            extract_access_modifier("private class MyClass"),
-            AccessModifier::Private
+            extract("internal static protected"),
+            AccessModifier::ProtectedInternal
         );
         assert_eq!(
-            extract_access_modifier("protected class MyClass"),
-            AccessModifier::Protected
+            extract("private protected"),
+            AccessModifier::PrivateProtected
         );
+        assert_eq!(extract(""), AccessModifier::Private);
         assert_eq!(
-            extract_access_modifier("internal class MyClass"),
+            extract_access_modifier(&[], AccessModifier::Internal),
             AccessModifier::Internal
         );
+        assert_eq!(
+            AccessModifier::ProtectedInternal.to_string(),
+            "protected internal"
+        );
     }
 
     #[test]
-    fn test_extract_single_line_docstring() {
-        let mut extractor = DocstringExtractor::new();
-        let line = "/// <summary>This is a single-line summary</summary>";
+    fn test_parse_default_access_modifiers() {
+        let source = r#"
+class TopLevel {
+    int field;
+    class Nested { }
+    protected internal void Shared() { }
+}
+delegate void Handler();
+interface IService {
+    void Run();
+    private protected int Helper() => 0;
+}
+"#;
+        let constructs = parse_cs_source(source, &[]);
+        assert_eq!(constructs[0].access_modifier, AccessModifier::Internal);
+        assert_eq!(
+            constructs[0].members[0].access_modifier,
+            AccessModifier::Private
+        );
+        assert_eq!(
+            constructs[0].members[1].access_modifier,
+            AccessModifier::ProtectedInternal
+        );
+        assert_eq!(constructs[1].access_modifier, AccessModifier::Private);
+        assert_eq!(constructs[2].access_modifier, AccessModifier::Internal);
+        let service = &constructs[3];
+        assert_eq!(service.members[0].access_modifier, AccessModifier::Public);
         assert_eq!(
-            extractor.extract_docstring(line),
-            Some("This is a single-line summary".to_string())
+            service.members[1].access_modifier,
+            AccessModifier::PrivateProtected
         );
     }
 
     #[test]
-    fn test_extract_multi_line_docstring() {
-        let mut extractor = DocstringExtractor::new();
-        let lines = vec![
-            "/// <summary>",
-            "/// This is a multi-line",
-            "/// summary",
-            "/// </summary>",
-        ];
-        let mut result = None;
-        for line in lines {
-            result = extractor.extract_docstring(line);
-        }
-        assert_eq!(result, Some("This is a multi-line summary".to_string()));
+    fn test_parse_ignores_comments_and_strings() {
+        let source = r#"
+// public class CommentedOutClass { }
+/* class InComment { } */
+public struct PublicStruct {
+    /* Inline comment */ public int Field;
+    string s = "class NotAClass { }";
+    string v = @"struct ""NotAStruct""";
+    string i = $"{(x ? "enum" : "interface")} NotAnything";
+}
+"#;
+        let constructs = parse_cs_source(source, &[]);
+        assert_eq!(constructs.len(), 1);
+        assert_eq!(constructs[0].name, "PublicStruct");
+        assert_eq!(constructs[0].construct_type, ConstructType::Struct);
     }
 
     #[test]
-    fn test_extract_single_line_inside_multi_line_docstring() {
-        let mut extractor = DocstringExtractor::new();
-        let lines = vec![
-            "/// <summary>This is a single-line summary inside multi-line",
-            "/// but not ended yet",
-            "/// </summary>",
-        ];
-        let mut result = None;
-        for line in lines {
-            result = extractor.extract_docstring(line);
-        }
+    fn test_parse_ignores_constraint_keywords() {
+        let source =
+            "public class Repo<T> where T : class, new() { void M<U>() where U : struct { } }";
+        let constructs = parse_cs_source(source, &[]);
+        assert_eq!(constructs.len(), 1);
+        assert_eq!(constructs[0].name, "Repo");
+        assert!(constructs[0].base_types.is_empty());
+    }
+
+    #[test]
+    fn test_parse_docstring_attached_to_construct() {
+        let source = "/// <summary>Documented.</summary>\n[Serializable]\npublic class A { }\npublic class B { }";
+        let constructs = parse_cs_source(source, &[]);
+        assert_eq!(
+            summary_of(&constructs[0].doc),
+            Some("Documented.".to_string())
+        );
+        assert_eq!(constructs[1].doc, None);
+    }
+
+    #[test]
+    fn test_parse_members() {
+        let source = r#"
+public class Account : IDisposable {
+    /// <summary>Creates an account.</summary>
+    public Account(string owner, decimal balance = 0m) : base() { }
+    public string Owner { get; private set; } = "";
+    public decimal Balance => balance;
+    private decimal balance, limit = 10, fee;
+    protected readonly Dictionary<string, int> counts = new Dictionary<string, int>();
+    public event EventHandler Changed;
+    internal int this[int index] { get => 0; set { } }
+    public static Account operator +(Account a, Account b) => a;
+    public static implicit operator decimal(Account a) => a.balance;
+    public T Get<T>(ref int count, params object[] args) where T : class { return null; }
+    void IDisposable.Dispose() { }
+    ~Account() { }
+}
+"#;
+        let constructs = parse_cs_source(source, &[]);
+        let members = &constructs[0].members;
+        let summary: Vec<(MemberType, &str)> = members
+            .iter()
+            .map(|m| (m.member_type, m.name.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (MemberType::Constructor, "Account"),
+                (MemberType::Property, "Owner"),
+                (MemberType::Property, "Balance"),
+                (MemberType::Field, "balance"),
+                (MemberType::Field, "limit"),
+                (MemberType::Field, "fee"),
+                (MemberType::Field, "counts"),
+                (MemberType::Event, "Changed"),
+                (MemberType::Indexer, "this"),
+                (MemberType::Operator, "operator +"),
+                (MemberType::Operator, "implicit operator decimal"),
+                (MemberType::Method, "Get"),
+                (MemberType::Method, "Dispose"),
+            ]
+        );
+
+        let constructor = &members[0];
+        assert_eq!(
+            summary_of(&constructor.doc),
+            Some("Creates an account.".to_string())
+        );
+        assert_eq!(
+            constructor.signature,
+            "public Account(string owner, decimal balance = 0m)"
+        );
+        assert_eq!(
+            constructor.parameters[1].default_value,
+            Some("0m".to_string())
+        );
+
+        assert_eq!(members[1].accessors, vec!["get", "private set"]);
+        assert_eq!(
+            members[1].signature,
+            "public string Owner { get; private set; }"
+        );
+        assert_eq!(members[2].accessors, vec!["get"]);
+        assert_eq!(members[4].access_modifier, AccessModifier::Private);
+        assert_eq!(members[5].signature, "private decimal fee");
+        assert_eq!(
+            members[6].type_name,
+            Some("Dictionary<string, int>".to_string())
+        );
+        assert_eq!(members[8].access_modifier, AccessModifier::Internal);
+        assert_eq!(members[8].parameters[0].type_name, "int");
+
+        let method = &members[11];
+        assert_eq!(method.type_name, Some("T".to_string()));
+        assert_eq!(
+            method.parameters,
+            vec![
+                Parameter {
+                    name: "count".to_string(),
+                    type_name: "int".to_string(),
+                    modifier: Some("ref".to_string()),
+                    default_value: None,
+                },
+                Parameter {
+                    name: "args".to_string(),
+                    type_name: "object[]".to_string(),
+                    modifier: Some("params".to_string()),
+                    default_value: None,
+                },
+            ]
+        );
+        assert_eq!(
+            method.signature,
+            "public T Get<T>(ref int count, params object[] args) where T : class"
+        );
+        assert_eq!(members[12].type_name, Some("void".to_string()));
+    }
+
+    #[test]
+    fn test_parse_tuple_members() {
+        let source = r#"
+public class Pairs {
+    public (int, string) GetPair() => (1, "a");
+    internal static (int Count, string Name)[] GetPairs<T>((int, int) range) => null;
+    public (int X, int Y) Position { get; set; }
+    private (int, int)? last;
+    public static (int, int) operator +(Pairs a, Pairs b) => (0, 0);
+}
+"#;
+        let constructs = parse_cs_source(source, &[]);
+        let members: Vec<(MemberType, &str, AccessModifier, Option<&str>)> = constructs[0]
+            .members
+            .iter()
+            .map(|m| {
+                (
+                    m.member_type,
+                    m.name.as_str(),
+                    m.access_modifier,
+                    m.type_name.as_deref(),
+                )
+            })
+            .collect();
         assert_eq!(
-            result,
-            Some("This is a single-line summary inside multi-line but not ended yet".to_string())
+            members,
+            vec![
+                (
+                    MemberType::Method,
+                    "GetPair",
+                    AccessModifier::Public,
+                    Some("(int, string)")
+                ),
+                (
+                    MemberType::Method,
+                    "GetPairs",
+                    AccessModifier::Internal,
+                    Some("(int Count, string Name)[]")
+                ),
+                (
+                    MemberType::Property,
+                    "Position",
+                    AccessModifier::Public,
+                    Some("(int X, int Y)")
+                ),
+                (
+                    MemberType::Field,
+                    "last",
+                    AccessModifier::Private,
+                    Some("(int, int)?")
+                ),
+                (
+                    MemberType::Operator,
+                    "operator +",
+                    AccessModifier::Public,
+                    Some("(int, int)")
+                ),
+            ]
+        );
+        let members = &constructs[0].members;
+        assert_eq!(members[0].signature, "public (int, string) GetPair()");
+        assert_eq!(members[1].parameters[0].type_name, "(int, int)");
+        assert_eq!(
+            members[2].signature,
+            "public (int X, int Y) Position { get; set; }"
         );
     }
 
     #[test]
-    fn test_skip_due_to_comment() {
-        let mut inside_multiline_comment = false;
-
-        // Test single-line comment
-        let line = "// This is a single-line comment";
-        assert!(skip_due_to_comment(line, &mut inside_multiline_comment));
-        assert!(!inside_multiline_comment);
-
-        // Test XML documentation comment
-        let line = "/// This is an XML doc comment";
-        assert!(skip_due_to_comment(line, &mut inside_multiline_comment));
-        assert!(!inside_multiline_comment);
-
-        // Test multi-line comment start and end on the same line
-        let line = "/* This is a multi-line comment */";
-        assert!(skip_due_to_comment(line, &mut inside_multiline_comment));
-        assert!(!inside_multiline_comment);
-
-        // Test multi-line comment start
-        let line = "/* This is a multi-line comment";
-        assert!(skip_due_to_comment(line, &mut inside_multiline_comment));
-        assert!(inside_multiline_comment);
-
-        // Test multi-line comment end
-        let line = "still in multi-line comment */";
-        assert!(skip_due_to_comment(line, &mut inside_multiline_comment));
-        assert!(!inside_multiline_comment);
-
-        // Test line with construct type and comment after it
-        let line = "public class MyClass // This is a comment";
-        assert!(!skip_due_to_comment(line, &mut inside_multiline_comment));
-        assert!(!inside_multiline_comment);
-
-        // Test line with construct type and comment before it
-        let line = "// This is a comment before class definition\npublic class MyClass";
-        assert!(skip_due_to_comment(line, &mut inside_multiline_comment));
-        assert!(!inside_multiline_comment);
-
-        // Test line with construct type and no comment
-        let line = "public class MyClass";
-        assert!(!skip_due_to_comment(line, &mut inside_multiline_comment));
-        assert!(!inside_multiline_comment);
-
-        // Test line with construct type and comment before keyword for other construct types
-        let line = "public struct MyStruct // Comment";
-        assert!(!skip_due_to_comment(line, &mut inside_multiline_comment));
-        assert!(!inside_multiline_comment);
-
-        let line = "public enum MyEnum // Comment";
-        assert!(!skip_due_to_comment(line, &mut inside_multiline_comment));
-        assert!(!inside_multiline_comment);
-
-        let line = "public interface MyInterface // Comment";
-        assert!(!skip_due_to_comment(line, &mut inside_multiline_comment));
-        assert!(!inside_multiline_comment);
+    fn test_parse_members_of_nested_types() {
+        let source = "class Outer { int a; class Inner { int b; void M() { int local; } } int c; }";
+        let constructs = parse_cs_source(source, &[]);
+        let names = |i: usize| -> Vec<String> {
+            constructs[i]
+                .members
+                .iter()
+                .map(|m| m.name.clone())
+                .collect()
+        };
+        assert_eq!(names(0), vec!["a", "c"]);
+        assert_eq!(names(1), vec!["b", "M"]);
+    }
+
+    #[test]
+    fn test_parse_records_and_delegates() {
+        let source = r#"
+/// <param name="Age">The age.</param>
+public record Person(string Name, int Age);
+public record class Employee(string Name) : Person(Name, 0) { public int Id { get; init; } }
+public record struct Point(int X, int Y);
+public readonly record struct Size(int Width, int Height);
+public delegate TResult Converter<T, TResult>(T input, int count = 1);
+"#;
+        let constructs = parse_cs_source(source, &[]);
+        let summary: Vec<(ConstructType, &str)> = constructs
+            .iter()
+            .map(|c| (c.construct_type.clone(), c.name.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ConstructType::Record, "Person"),
+                (ConstructType::Record, "Employee"),
+                (ConstructType::RecordStruct, "Point"),
+                (ConstructType::RecordStruct, "Size"),
+                (ConstructType::Delegate, "Converter"),
+            ]
+        );
+
+        assert_eq!(constructs[0].parameters.len(), 2);
+        assert_eq!(
+            summary_of(&constructs[0].members[1].doc),
+            Some("The age.".to_string())
+        );
+        assert_eq!(
+            constructs[0].members[0].signature,
+            "public string Name { get; init; }"
+        );
+        let employee_members: Vec<&str> = constructs[1]
+            .members
+            .iter()
+            .map(|m| m.name.as_str())
+            .collect();
+        assert_eq!(employee_members, vec!["Name", "Id"]);
+        assert_eq!(constructs[2].members[0].accessors, vec!["get", "set"]);
+        assert_eq!(constructs[3].members[0].accessors, vec!["get", "init"]);
+
+        let delegate = &constructs[4];
+        assert_eq!(delegate.access_modifier, AccessModifier::Public);
+        assert_eq!(delegate.return_type, Some("TResult".to_string()));
+        assert_eq!(delegate.parameters[1].default_value, Some("1".to_string()));
+    }
+
+    #[test]
+    fn test_parse_namespaces() {
+        let source = r#"
+class Global { }
+namespace App.Core {
+    class Logger { }
+    namespace Logging { class Logger { } }
+    class Service { }
+}
+"#;
+        let constructs = parse_cs_source(source, &[]);
+        let names: Vec<String> = constructs.iter().map(|c| c.full_name()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "Global",
+                "App.Core.Logger",
+                "App.Core.Logging.Logger",
+                "App.Core.Service"
+            ]
+        );
+
+        let constructs = parse_cs_source(
+            "namespace App.Models;\n\npublic record User(string Name);",
+            &[],
+        );
+        assert_eq!(constructs[0].namespace, Some("App.Models".to_string()));
+    }
+
+    #[test]
+    fn test_parse_nested_types() {
+        let source = fs::read_to_string("test_data/NestedExample.cs").unwrap();
+        let constructs = parse_cs_source(&source, &[]);
+        let outer = &constructs[0];
+        assert_eq!(outer.parent, None);
+        assert_eq!(
+            outer.children,
+            vec!["InnerClass", "InnerStruct", "InnerEnum", "InnerInterface"]
+        );
+        assert_eq!(constructs[1].parent, Some("OuterClass".to_string()));
+        assert_eq!(constructs[1].qualified_name(), "OuterClass.InnerClass");
+        assert_eq!(constructs[1].depth(), 1);
+
+        let constructs = parse_cs_source(
+            "namespace N { class A { class B { delegate void C(); } } }",
+            &[],
+        );
+        assert_eq!(constructs[2].full_name(), "N.A.B.C");
+        assert_eq!(constructs[2].depth(), 2);
+    }
+
+    #[test]
+    fn test_parse_base_types() {
+        let source = "record R(int X) : Base<int>(X), IEquatable<R> where T : class { }";
+        let constructs = parse_cs_source(source, &[]);
+        assert_eq!(constructs[0].base_types, vec!["Base<int>", "IEquatable<R>"]);
+    }
+
+    #[test]
+    fn test_parse_generics_and_constraints() {
+        let source = r#"
+interface IRepo<in T> { }
+class RepoBase { }
+class Repo<[Marker] T, TKey> : RepoBase, IRepo<T>, IDisposable where T : class, IEntity, new() where TKey : struct { }
+class Service : IRepo<int>, Other.Base<int> { }
+class Legacy : Component, ISite { }
+struct Point : IEquatable<Point> { }
+delegate TResult Factory<out TResult>(int seed) where TResult : new();
+"#;
+        let mut constructs = parse_cs_source(source, &[]);
+        classify_base_types(&mut constructs);
+
+        assert_eq!(constructs[0].type_parameters, vec!["in T"]);
+        let repo = &constructs[2];
+        assert_eq!(repo.type_parameters, vec!["T", "TKey"]);
+        assert_eq!(
+            repo.constraints,
+            vec![
+                TypeConstraint {
+                    type_parameter: "T".to_string(),
+                    constraints: vec![
+                        "class".to_string(),
+                        "IEntity".to_string(),
+                        "new()".to_string()
+                    ],
+                },
+                TypeConstraint {
+                    type_parameter: "TKey".to_string(),
+                    constraints: vec!["struct".to_string()],
+                },
+            ]
+        );
+        assert_eq!(repo.base_class, Some("RepoBase".to_string()));
+        assert_eq!(repo.interfaces, vec!["IRepo<T>", "IDisposable"]);
+        assert_eq!(constructs[3].base_class, None);
+        assert_eq!(
+            constructs[3].interfaces,
+            vec!["IRepo<int>", "Other.Base<int>"]
+        );
+        assert_eq!(constructs[4].base_class, Some("Component".to_string()));
+        assert_eq!(constructs[5].base_class, None);
+        assert_eq!(constructs[5].interfaces, vec!["IEquatable<Point>"]);
+
+        let factory = &constructs[6];
+        assert_eq!(factory.name, "Factory");
+        assert_eq!(factory.type_parameters, vec!["out TResult"]);
+        assert_eq!(factory.constraints[0].constraints, vec!["new()"]);
+    }
+
+    #[test]
+    fn test_parse_modifiers() {
+        let source = r#"
+public abstract partial class Shape {
+    protected abstract double Area();
+    public static readonly int Sides, Corners;
+}
+internal static unsafe class Native { }
+"#;
+        let constructs = parse_cs_source(source, &[]);
+        let shape = &constructs[0];
+        assert!(shape.modifiers.is_abstract && shape.modifiers.is_partial);
+        assert!(!shape.modifiers.is_static);
+        assert_eq!(shape.modifiers.keywords(), vec!["abstract", "partial"]);
+        assert!(shape.members[0].modifiers.is_abstract);
+        assert!(shape.members[2].modifiers.is_static && shape.members[2].modifiers.is_readonly);
+        assert_eq!(constructs[1].modifiers.keywords(), vec!["static", "unsafe"]);
+        assert_eq!(constructs[1].modifiers.has("static"), Some(true));
+        assert_eq!(constructs[1].modifiers.has("sealed"), Some(false));
+        assert_eq!(constructs[1].modifiers.has("public"), None);
+    }
+
+    #[test]
+    fn test_parse_enum_members() {
+        let source = r#"
+[System.Flags, Serializable]
+public enum Options : ulong {
+    /// <summary>Nothing set.</summary>
+    None = 0,
+    [Obsolete] First = 1 << 0,
+    Second = (First | 2),
+    Third
+}
+enum Empty { }
+"#;
+        let constructs = parse_cs_source(source, &[]);
+        let options = &constructs[0];
+        assert!(options.is_flags());
+        assert_eq!(options.underlying_type, Some("ulong".to_string()));
+        assert!(options.base_types.is_empty());
+        let members: Vec<(&str, Option<&str>)> = options
+            .enum_members
+            .iter()
+            .map(|m| (m.name.as_str(), m.value.as_deref()))
+            .collect();
+        assert_eq!(
+            members,
+            vec![
+                ("None", Some("0")),
+                ("First", Some("1 << 0")),
+                ("Second", Some("(First | 2)")),
+                ("Third", None),
+            ]
+        );
+        assert_eq!(
+            summary_of(&options.enum_members[0].doc),
+            Some("Nothing set.".to_string())
+        );
+        assert!(!constructs[1].is_flags());
+        assert!(constructs[1].enum_members.is_empty());
+        assert_eq!(constructs[1].underlying_type, None);
+    }
+
+    #[test]
+    fn test_parse_attributes() {
+        let source = r#"
+[assembly: InternalsVisibleTo("Tests")]
+[Serializable, DebuggerDisplay("{Name}")]
+[System.Obsolete(
+    "Use Service2",
+    error: true)]
+public class Service {
+    [JsonIgnore] [Required]
+    public string Name { get; set; }
+    [return: NotNull]
+    public string Describe() => Name;
+    [Conditional("DEBUG")] void Trace() { }
+}
+"#;
+        let constructs = parse_cs_source(source, &[]);
+        let service = &constructs[0];
+        let names: Vec<&str> = service.attributes.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["Serializable", "DebuggerDisplay", "System.Obsolete"]
+        );
+        assert_eq!(service.attributes[1].arguments, vec!["\"{Name}\""]);
+        let obsolete = service.obsolete().unwrap();
+        assert_eq!(obsolete.arguments, vec!["\"Use Service2\"", "error: true"]);
+        assert_eq!(obsolete.message(), Some("Use Service2".to_string()));
+        assert!(service.attributes[0].is("Serializable"));
+
+        let name = &service.members[0];
+        assert_eq!(name.attributes.len(), 2);
+        assert_eq!(name.attributes[1].to_string(), "[Required]");
+        let describe = &service.members[1];
+        assert_eq!(describe.attributes[0].target, Some("return".to_string()));
+        assert_eq!(describe.attributes[0].to_string(), "[return: NotNull]");
+        assert_eq!(
+            service.members[2].attributes[0].to_string(),
+            "[Conditional(\"DEBUG\")]"
+        );
+    }
+
+    #[test]
+    fn test_parse_preprocessor_directives() {
+        let source = fs::read_to_string("test_data/PreprocessorExample.cs").unwrap();
+        let release = parse_cs_source(&source, &[]);
+        assert_eq!(release.len(), 1);
+        let logger = &release[0];
+        assert_eq!(logger.region, Some("Logging".to_string()));
+        assert_eq!(logger.members.len(), 1);
+        assert_eq!(
+            logger.members[0].signature,
+            "public void Log(string message)"
+        );
+        assert_eq!(logger.members[0].region, Some("Output".to_string()));
+
+        let defines = vec!["DEBUG".to_string(), "UNITY_EDITOR".to_string()];
+        let editor = parse_cs_source(&source, &defines);
+        let names: Vec<&str> = editor.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Logger", "EditorTools"]);
+        assert_eq!(editor[1].region, None);
+        let members: Vec<(&str, usize)> = editor[0]
+            .members
+            .iter()
+            .map(|m| (m.name.as_str(), m.parameters.len()))
+            .collect();
+        assert_eq!(members, vec![("Trace", 1), ("Log", 2)]);
+        assert_eq!(editor[0].members[0].region, Some("Logging".to_string()));
+    }
+
+    #[test]
+    fn test_parse_source_spans() {
+        let source = "namespace App\n{\n    /// <summary>Doc.</summary>\n    [Serializable]\n    public class Outer\n    {\n        public delegate void Handler();\n    }\n}\n";
+        let constructs = parse_cs_source(source, &[]);
+        let span = |c: &ConstructInfo| {
+            let span = c.span.as_ref().unwrap();
+            (
+                span.start_line,
+                span.start_column,
+                span.end_line,
+                span.end_column,
+            )
+        };
+        assert_eq!(span(&constructs[0]), (4, 5, 8, 6));
+        assert_eq!(span(&constructs[1]), (7, 9, 7, 40));
+    }
+
+    #[test]
+    fn test_merge_partial_types() {
+        let test_dir = PathBuf::from("_merge_partial_test_data");
+        fs::create_dir_all(&test_dir).unwrap();
+        fs::write(
+            test_dir.join("a.cs"),
+            "namespace A { partial class Service : IService { int a; partial void OnInit(); } }\n\
+             namespace A { class Logger { } }",
+        )
+        .unwrap();
+        fs::write(
+            test_dir.join("b.cs"),
+            "namespace A {\n\
+             /// <summary>Documented part.</summary>\n\
+             public partial class Service : IDisposable, IService {\n\
+                 /// <summary>Init hook.</summary>\n\
+                 partial void OnInit() { }\n\
+                 int b;\n\
+             }\n\
+             }\n\
+             namespace B { class Logger { } }",
+        )
+        .unwrap();
+
+        let mut cs_files = find_cs_files(&test_dir);
+        cs_files.sort();
+        let constructs = parse_cs_files(&test_dir, cs_files, &[]);
+        fs::remove_dir_all(&test_dir).unwrap();
+
+        let names: Vec<String> = constructs.iter().map(|c| c.full_name()).collect();
+        assert_eq!(names, vec!["A.Service", "A.Logger", "B.Logger"]);
+
+        let service = &constructs[0];
+        assert_eq!(
+            summary_of(&service.doc),
+            Some("Documented part.".to_string())
+        );
+        assert_eq!(service.access_modifier, AccessModifier::Public);
+        assert_eq!(service.base_types, vec!["IService", "IDisposable"]);
+        let members: Vec<&str> = service.members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(members, vec!["a", "OnInit", "b"]);
+        assert_eq!(
+            summary_of(&service.members[1].doc),
+            Some("Init hook.".to_string())
+        );
+        assert_eq!(service.files.len(), 2);
     }
 }
@@ -0,0 +1,267 @@
+use std::collections::HashSet;
+
+use crate::lexer::{Token, TokenKind};
+
+// One `#if ... #endif` block being processed.
+struct Conditional {
+    // Whether the surrounding code is compiled at all
+    parent_active: bool,
+    // Whether the current branch is compiled
+    active: bool,
+    // Whether one of the branches so far was taken, so later `#elif`/`#else` branches are not
+    taken: bool,
+}
+
+// Drops the tokens of the `#if`/`#elif`/`#else` branches that are not compiled with the given
+// symbols defined. `#define` and `#undef` change the symbols for the rest of the file. Other
+// directives such as `#region` are kept as long as they are in compiled code.
+pub fn preprocess(tokens: Vec<Token>, defines: &[String]) -> Vec<Token> {
+    let mut symbols: HashSet<String> = defines.iter().cloned().collect();
+    let mut conditionals: Vec<Conditional> = Vec::new();
+    let mut result = Vec::new();
+
+    for token in tokens {
+        let active = conditionals.last().is_none_or(|c| c.active);
+        if token.kind != TokenKind::Directive {
+            if active {
+                result.push(token);
+            }
+            continue;
+        }
+
+        let directive = token.text.trim_start_matches('#').trim_start();
+        let (keyword, argument) = directive
+            .split_once(char::is_whitespace)
+            .map_or((directive, ""), |(keyword, argument)| {
+                (keyword, argument.trim())
+            });
+        match keyword {
+            "if" => {
+                let condition = active && evaluate(argument, &symbols);
+                conditionals.push(Conditional {
+                    parent_active: active,
+                    active: condition,
+                    taken: condition,
+                });
+            }
+            "elif" => {
+                if let Some(conditional) = conditionals.last_mut() {
+                    conditional.active = conditional.parent_active
+                        && !conditional.taken
+                        && evaluate(argument, &symbols);
+                    conditional.taken |= conditional.active;
+                }
+            }
+            "else" => {
+                if let Some(conditional) = conditionals.last_mut() {
+                    conditional.active = conditional.parent_active && !conditional.taken;
+                    conditional.taken = true;
+                }
+            }
+            "endif" => {
+                conditionals.pop();
+            }
+            "define" if active => {
+                symbols.insert(argument.to_string());
+            }
+            "undef" if active => {
+                symbols.remove(argument);
+            }
+            _ if active => result.push(token),
+            _ => {}
+        }
+    }
+    result
+}
+
+// Evaluates a preprocessor expression such as `DEBUG && !(UNITY_EDITOR || false)`. Malformed
+// expressions count as false.
+fn evaluate(expression: &str, symbols: &HashSet<String>) -> bool {
+    let mut parser = ExpressionParser {
+        tokens: split_expression(expression),
+        pos: 0,
+        symbols,
+    };
+    let value = parser.parse_or();
+    match value {
+        Some(value) if parser.pos == parser.tokens.len() => value,
+        _ => {
+            eprintln!(
+                "Warning: could not evaluate preprocessor expression `{}`",
+                expression
+            );
+            false
+        }
+    }
+}
+
+fn split_expression(expression: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        if c == '/' && chars.peek() == Some(&'/') {
+            break;
+        }
+        if c.is_alphanumeric() || c == '_' {
+            let mut identifier = c.to_string();
+            while let Some(&next) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_') {
+                identifier.push(next);
+                chars.next();
+            }
+            tokens.push(identifier);
+            continue;
+        }
+        let mut operator = c.to_string();
+        if let Some(&next) = chars.peek() {
+            if matches!((c, next), ('&', '&') | ('|', '|') | ('=', '=') | ('!', '=')) {
+                operator.push(next);
+                chars.next();
+            }
+        }
+        tokens.push(operator);
+    }
+    tokens
+}
+
+struct ExpressionParser<'a> {
+    tokens: Vec<String>,
+    pos: usize,
+    symbols: &'a HashSet<String>,
+}
+
+impl ExpressionParser<'_> {
+    fn eat(&mut self, text: &str) -> bool {
+        let matches = self.tokens.get(self.pos).is_some_and(|t| t == text);
+        if matches {
+            self.pos += 1;
+        }
+        matches
+    }
+
+    fn parse_or(&mut self) -> Option<bool> {
+        let mut value = self.parse_and()?;
+        while self.eat("||") {
+            value |= self.parse_and()?;
+        }
+        Some(value)
+    }
+
+    fn parse_and(&mut self) -> Option<bool> {
+        let mut value = self.parse_equality()?;
+        while self.eat("&&") {
+            value &= self.parse_equality()?;
+        }
+        Some(value)
+    }
+
+    fn parse_equality(&mut self) -> Option<bool> {
+        let mut value = self.parse_unary()?;
+        loop {
+            if self.eat("==") {
+                value = value == self.parse_unary()?;
+            } else if self.eat("!=") {
+                value = value != self.parse_unary()?;
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    fn parse_unary(&mut self) -> Option<bool> {
+        if self.eat("!") {
+            return Some(!self.parse_unary()?);
+        }
+        if self.eat("(") {
+            let value = self.parse_or()?;
+            return self.eat(")").then_some(value);
+        }
+        let token = self.tokens.get(self.pos)?;
+        let value = match token.as_str() {
+            "true" => true,
+            "false" => false,
+            symbol if symbol.starts_with(|c: char| c.is_alphabetic() || c == '_') => {
+                self.symbols.contains(symbol)
+            }
+            _ => return None,
+        };
+        self.pos += 1;
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tokenize;
+
+    fn active_identifiers(source: &str, defines: &[&str]) -> Vec<String> {
+        let defines: Vec<String> = defines.iter().map(|d| d.to_string()).collect();
+        preprocess(tokenize(source), &defines)
+            .into_iter()
+            .filter(|t| t.kind == TokenKind::Identifier)
+            .map(|t| t.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_preprocess_branches() {
+        let source = "
+#if DEBUG
+a
+#elif TRACE && !UNITY_EDITOR
+b
+#else
+c
+#endif
+d
+";
+        assert_eq!(active_identifiers(source, &["DEBUG"]), vec!["a", "d"]);
+        assert_eq!(active_identifiers(source, &["TRACE"]), vec!["b", "d"]);
+        assert_eq!(
+            active_identifiers(source, &["TRACE", "UNITY_EDITOR"]),
+            vec!["c", "d"]
+        );
+        assert_eq!(
+            active_identifiers(source, &["DEBUG", "TRACE"]),
+            vec!["a", "d"]
+        );
+    }
+
+    #[test]
+    fn test_preprocess_nesting_and_defines() {
+        let source = "
+#define FEATURE
+#if false
+  #if FEATURE
+  a
+  #else
+  b
+  #endif
+#elif (FEATURE || X) == true
+  c
+  #undef FEATURE
+  #if FEATURE
+  d
+  #endif
+#endif
+";
+        assert_eq!(active_identifiers(source, &[]), vec!["c"]);
+    }
+
+    #[test]
+    fn test_preprocess_keeps_other_directives() {
+        let tokens = preprocess(
+            tokenize("#region Api\nclass A { }\n#if X\n#region Hidden\n#endif\n#endregion"),
+            &[],
+        );
+        let directives: Vec<&str> = tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::Directive)
+            .map(|t| t.text.as_str())
+            .collect();
+        assert_eq!(directives, vec!["#region Api", "#endregion"]);
+    }
+}
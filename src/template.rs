@@ -0,0 +1,1878 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+// The data a template is rendered with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Bool(bool),
+    List(Vec<Value>),
+    Object(BTreeMap<String, Value>),
+}
+
+impl Value {
+    pub fn object<'k>(entries: impl IntoIterator<Item = (&'k str, Value)>) -> Value {
+        Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(entries) => entries.get(key),
+            _ => None,
+        }
+    }
+
+    // Empty text and lists are false, as are missing values.
+    fn is_truthy(&self) -> bool {
+        match self {
+            Value::Text(text) => !text.is_empty(),
+            Value::Bool(value) => *value,
+            Value::List(items) => !items.is_empty(),
+            Value::Object(_) => true,
+        }
+    }
+
+    // Lists are joined with `, ` and objects print their name, e.g. `{{base_types}}` or
+    // `{{members}}`.
+    pub fn render(&self) -> String {
+        match self {
+            Value::Text(text) => text.clone(),
+            Value::Bool(value) => value.to_string(),
+            Value::List(items) => items
+                .iter()
+                .map(Value::render)
+                .collect::<Vec<_>>()
+                .join(", "),
+            Value::Object(_) => self.get("name").map(Value::render).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(text: &str) -> Self {
+        Value::Text(text.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(text: String) -> Self {
+        Value::Text(text)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(items: Vec<T>) -> Self {
+        Value::List(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or_else(|| Value::Text(String::new()), Into::into)
+    }
+}
+
+// Line and column of a tag in the template, starting at 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TemplateError {
+    pub message: String,
+    // The partial the error is in, `None` for the main template
+    pub file: Option<String>,
+    pub position: Position,
+}
+
+impl TemplateError {
+    // Attributes the error to `file` unless it is already known to be in another file.
+    pub fn in_file(mut self, file: Option<&str>) -> Self {
+        if self.file.is_none() {
+            self.file = file.map(String::from);
+        }
+        self
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file)?;
+        }
+        write!(
+            f,
+            "{}:{}: {}",
+            self.position.line, self.position.column, self.message
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String, Position),
+    Variable {
+        path: String,
+        filters: Vec<FilterCall>,
+        position: Position,
+    },
+    // `otherwise` is rendered when the list is empty or missing. The filters can reorder or group
+    // the list, e.g. `{{#each classes | sort(name)}}`.
+    Each {
+        path: String,
+        filters: Vec<FilterCall>,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+        position: Position,
+    },
+    // The body of the first branch whose condition holds, `otherwise` if none does
+    If {
+        branches: Vec<(Condition, Vec<Node>)>,
+        otherwise: Vec<Node>,
+    },
+    // `{{> name}}`, rendered with the current item
+    Partial {
+        name: String,
+        position: Position,
+    },
+    // `{% block name %} ... {% endblock %}`, which templates extending this one can override
+    Block {
+        name: String,
+        body: Vec<Node>,
+    },
+    // Nodes from another file, i.e. a base template or the blocks overriding its blocks
+    FromFile {
+        file: Option<String>,
+        nodes: Vec<Node>,
+    },
+}
+
+// A partial included with `{{> name}}`, either defined with `{{#partial name}} ... {{/partial}}`
+// or loaded from a file.
+#[derive(Debug, Clone)]
+struct Partial {
+    nodes: Vec<Node>,
+    // The partial file it is defined in, `None` for the main template
+    file: Option<String>,
+}
+
+// A filter applied to a variable, e.g. `truncate(80)` in `{{summary | truncate(80)}}`.
+#[derive(Debug, Clone)]
+struct FilterCall {
+    name: String,
+    arguments: Vec<String>,
+}
+
+// Transforms a value with the arguments of a filter call, or explains what is wrong with them.
+pub type Filter = fn(&Value, &[String]) -> Result<Value, String>;
+
+pub type Filters = HashMap<&'static str, Filter>;
+
+// What a template can refer to, for `Template::check`.
+pub struct Schema<'a> {
+    // A context with an item in every list, standing in for the real one
+    pub context: &'a Value,
+    pub filters: &'a Filters,
+    // Variables starting with this prefix, e.g. `vars.`, are set by the user and not checked
+    pub user_prefix: &'a str,
+    // The names of the single-line `[name]` placeholders
+    pub placeholders: &'a [String],
+    // The words single-line `[if <name>]` and `[if !<name>]` conditions can test
+    pub conditions: &'a [String],
+}
+
+// Returns the file name and source of a partial by name.
+pub type LoadPartial<'a> = dyn Fn(&str) -> Result<(String, String), String> + 'a;
+
+// `path`, `!path`, `path == value` or `path != value`, e.g. `access_modifier == "public"`.
+#[derive(Debug, Clone)]
+struct Condition {
+    negated: bool,
+    path: String,
+    comparison: Option<(bool, String)>,
+    position: Position,
+}
+
+impl Condition {
+    fn parse(text: &str, position: Position) -> Result<Condition, TemplateError> {
+        let (left, comparison) = match text.find("==").or_else(|| text.find("!=")) {
+            Some(i) => {
+                let literal = text[i + 2..].trim();
+                let literal = unquote(literal);
+                (
+                    &text[..i],
+                    Some((&text[i..i + 2] == "==", literal.to_string())),
+                )
+            }
+            None => (text, None),
+        };
+        let left = left.trim();
+        let (negated, path) = match left.strip_prefix('!') {
+            Some(path) => (true, path.trim()),
+            None => (false, left),
+        };
+        if path.is_empty() || path.contains(char::is_whitespace) {
+            return Err(error(
+                &format!("invalid condition `{}`", text.trim()),
+                position,
+            ));
+        }
+        Ok(Condition {
+            negated,
+            path: path.to_string(),
+            comparison,
+            position,
+        })
+    }
+
+    fn evaluate(&self, scopes: &[Scope]) -> bool {
+        let value = lookup(&self.path, scopes);
+        let result = match &self.comparison {
+            Some((equal, literal)) => {
+                (value.map(|v| v.render()).unwrap_or_default() == *literal) == *equal
+            }
+            None => value.is_some_and(|v| v.is_truthy()),
+        };
+        result != self.negated
+    }
+}
+
+// How a run of nodes inside a block ended.
+enum BlockEnd {
+    Close,
+    // `{{else}}`, or `{{else if <condition>}}`
+    Else(Option<Condition>, Position),
+}
+
+// A template using `{{name}}` variables, `{{#each list}} ... {{/each}}` loops,
+// `{{#if condition}} ... {{else}} ... {{/if}}` blocks, `{{> name}}` partials and
+// `{% block name %}` blocks overridable by templates that `{% extends %}` it.
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+    partials: HashMap<String, Partial>,
+    // The file of the template, used in errors
+    file: Option<String>,
+    // The base template named by `{% extends %}`
+    extends: Option<(String, Position)>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Template, TemplateError> {
+        let pieces = strip_standalone_tags(split_pieces(source)?);
+        let mut parser = NodeParser {
+            pieces,
+            pos: 0,
+            partials: HashMap::new(),
+            blocks: HashSet::new(),
+            extends: None,
+        };
+        let (nodes, _) = parser.parse_nodes(None)?;
+        let template = Template {
+            nodes,
+            partials: parser.partials,
+            file: None,
+            extends: parser.extends,
+        };
+        template.check_partial_cycles()?;
+        Ok(template)
+    }
+
+    pub fn with_file(mut self, file: &str) -> Self {
+        self.file = Some(file.to_string());
+        for partial in self.partials.values_mut() {
+            partial.file.get_or_insert_with(|| file.to_string());
+        }
+        self
+    }
+
+    pub fn extends(&self) -> Option<(&str, Position)> {
+        self.extends
+            .as_ref()
+            .map(|(base, position)| (base.as_str(), *position))
+    }
+
+    // Fills the blocks of `base` with the blocks of this template. Anything outside of blocks in
+    // an extending template is left out, as it has nowhere to go.
+    pub fn extend(self, base: Template) -> Result<Template, TemplateError> {
+        let mut overrides = HashMap::new();
+        for (node, _) in walk(&self.nodes, None) {
+            if let Node::Block { name, body } = node {
+                overrides.insert(name.as_str(), body);
+            }
+        }
+        let mut used = HashSet::new();
+        let nodes = override_blocks(&base.nodes, &overrides, self.file.as_deref(), &mut used);
+        let mut unused: Vec<&&str> = overrides.keys().filter(|n| !used.contains(*n)).collect();
+        unused.sort();
+        if let Some(name) = unused.first() {
+            let position = self.extends.as_ref().map(|(_, p)| *p);
+            return Err(error(
+                &format!("block `{}` is not in the base template", name),
+                position.unwrap_or(Position { line: 1, column: 1 }),
+            )
+            .in_file(self.file.as_deref()));
+        }
+
+        let mut partials = base.partials;
+        partials.extend(self.partials);
+        let template = Template {
+            nodes: vec![Node::FromFile {
+                file: base.file,
+                nodes,
+            }],
+            partials,
+            file: self.file,
+            extends: None,
+        };
+        template.check_partial_cycles()?;
+        Ok(template)
+    }
+
+    // Loads the partials that are included but not defined in the template with `load`.
+    pub fn load_partials(&mut self, load: &LoadPartial) -> Result<(), TemplateError> {
+        let mut pending = included_partials(&self.nodes, self.file.as_deref());
+        for partial in self.partials.values() {
+            pending.extend(included_partials(&partial.nodes, partial.file.as_deref()));
+        }
+        while let Some((name, file, position)) = pending.pop() {
+            if self.partials.contains_key(&name) {
+                continue;
+            }
+            let (loaded_file, source) = load(&name).map_err(|message| TemplateError {
+                message: format!("cannot load partial `{}`: {}", name, message),
+                file,
+                position,
+            })?;
+            let loaded = Template::parse(&source)
+                .map_err(|e| e.in_file(Some(&loaded_file)))?
+                .with_file(&loaded_file);
+            pending.extend(included_partials(&loaded.nodes, Some(&loaded_file)));
+            for (inner, partial) in loaded.partials {
+                pending.extend(included_partials(&partial.nodes, Some(&loaded_file)));
+                self.partials.entry(inner).or_insert(partial);
+            }
+            self.partials.insert(
+                name,
+                Partial {
+                    nodes: loaded.nodes,
+                    file: Some(loaded_file),
+                },
+            );
+        }
+        self.check_partial_cycles()
+    }
+
+    fn check_partial_cycles(&self) -> Result<(), TemplateError> {
+        let mut names: Vec<&String> = self.partials.keys().collect();
+        names.sort();
+        let mut done = HashSet::new();
+        for name in names {
+            self.visit_partial(name, &mut Vec::new(), &mut done)?;
+        }
+        Ok(())
+    }
+
+    fn visit_partial<'a>(
+        &'a self,
+        name: &'a str,
+        stack: &mut Vec<&'a str>,
+        done: &mut HashSet<&'a str>,
+    ) -> Result<(), TemplateError> {
+        let Some(partial) = self.partials.get(name).filter(|_| !done.contains(name)) else {
+            return Ok(());
+        };
+        stack.push(name);
+        for (node, file) in walk(&partial.nodes, partial.file.as_deref()) {
+            let Node::Partial {
+                name: included,
+                position,
+            } = node
+            else {
+                continue;
+            };
+            if let Some(start) = stack.iter().position(|n| n == included) {
+                let mut cycle = stack[start..].to_vec();
+                cycle.push(included);
+                return Err(TemplateError {
+                    message: format!("partials include each other: {}", cycle.join(" > ")),
+                    file: file.map(String::from),
+                    position: *position,
+                });
+            }
+            self.visit_partial(included, stack, done)?;
+        }
+        stack.pop();
+        done.insert(name);
+        Ok(())
+    }
+
+    // The variables under `prefix`, e.g. `vars.`, that are printed without checking for them
+    // first with `{{#if <prefix><name>}}`, along with the partial and position they are first
+    // used at.
+    pub fn required_variables(&self, prefix: &str) -> Vec<(String, Option<String>, Position)> {
+        let mut collector = RequiredVariables {
+            prefix,
+            partials: &self.partials,
+            checked: Vec::new(),
+            required: Vec::new(),
+        };
+        collector.collect(&self.nodes, self.file.as_deref());
+        collector.required
+    }
+
+    // The single-line `[name]` placeholders in the text of the template and its partials, along
+    // with the partial and position they are first used at.
+    pub fn placeholders(&self) -> Vec<(String, Option<String>, Position)> {
+        let mut partials: Vec<(&String, &Partial)> = self.partials.iter().collect();
+        partials.sort_by_key(|(name, _)| *name);
+        let nodes = std::iter::once((&self.nodes, self.file.as_deref())).chain(
+            partials
+                .into_iter()
+                .map(|(_, p)| (&p.nodes, p.file.as_deref())),
+        );
+        let mut found: Vec<(String, Option<String>, Position)> = Vec::new();
+        for (node, file) in nodes.flat_map(|(nodes, file)| walk(nodes, file)) {
+            let Node::Text(text, position) = node else {
+                continue;
+            };
+            for (name, position) in find_placeholders(text, *position) {
+                if found.iter().all(|(n, _, _)| n != name) {
+                    found.push((name.to_string(), file.map(String::from), position));
+                }
+            }
+        }
+        found
+    }
+
+    // Replaces `[name]` placeholders in the text of the template with the given values, so that
+    // the values don't end up in the text of the data the template is rendered with.
+    pub fn fill_placeholders(&mut self, values: &BTreeMap<String, String>) {
+        fill_placeholders(&mut self.nodes, values);
+        for partial in self.partials.values_mut() {
+            fill_placeholders(&mut partial.nodes, values);
+        }
+    }
+
+    // Reports the variables, filters and `[name]` placeholders that are not in the schema, and
+    // filters called with the wrong arguments.
+    pub fn check(&self, schema: &Schema) -> Vec<TemplateError> {
+        let mut checker = Checker {
+            schema,
+            partials: &self.partials,
+            errors: Vec::new(),
+        };
+        let mut scopes = vec![Scope {
+            value: schema.context,
+            index: None,
+        }];
+        checker.check_nodes(&self.nodes, &mut scopes, self.file.as_deref());
+        checker.errors
+    }
+
+    pub fn render(&self, context: &Value, filters: &Filters) -> Result<String, TemplateError> {
+        let mut out = String::new();
+        let mut scopes = vec![Scope {
+            value: context,
+            index: None,
+        }];
+        let renderer = Renderer {
+            filters,
+            partials: &self.partials,
+        };
+        renderer
+            .render_nodes(&self.nodes, &mut scopes, &mut out)
+            .map_err(|e| e.in_file(self.file.as_deref()))?;
+        Ok(out)
+    }
+}
+
+#[derive(Debug)]
+enum Piece {
+    Text(String, Position),
+    Tag { content: String, position: Position },
+}
+
+// Splits the source into text and the contents of `{{ }}` tags.
+fn split_pieces(source: &str) -> Result<Vec<Piece>, TemplateError> {
+    let mut pieces = Vec::new();
+    let mut rest = source;
+    let mut position = Position { line: 1, column: 1 };
+    while let Some(start) = [rest.find("{{"), rest.find("{%")]
+        .into_iter()
+        .flatten()
+        .min()
+    {
+        let text = &rest[..start];
+        if !text.is_empty() {
+            pieces.push(Piece::Text(text.to_string(), position));
+        }
+        advance(&mut position, text);
+        let is_statement = rest[start..].starts_with("{%");
+        let close = if is_statement { "%}" } else { "}}" };
+        let Some(end) = rest[start..].find(close).map(|end| start + end) else {
+            return Err(error(
+                &format!("unclosed tag, expected `{}`", close),
+                position,
+            ));
+        };
+        let content = rest[start + 2..end].trim();
+        pieces.push(Piece::Tag {
+            content: match is_statement {
+                true => translate_statement(content, position)?,
+                false => content.to_string(),
+            },
+            position,
+        });
+        advance(&mut position, &rest[start..end + 2]);
+        rest = &rest[end + 2..];
+    }
+    if !rest.is_empty() {
+        pieces.push(Piece::Text(rest.to_string(), position));
+    }
+    Ok(pieces)
+}
+
+// `{% block name %}`, `{% endblock %}` and `{% extends "base.md" %}` are handled like the
+// `{{#block name}}`, `{{/block}}` and `{{%extends base.md}}` tags.
+fn translate_statement(content: &str, position: Position) -> Result<String, TemplateError> {
+    match split_keyword(content) {
+        ("block", name) => Ok(format!("#block {}", name)),
+        ("endblock", _) => Ok("/block".to_string()),
+        ("extends", base) => Ok(format!("%extends {}", unquote(base))),
+        _ => Err(error(
+            &format!("unknown tag `{{% {} %}}`", content),
+            position,
+        )),
+    }
+}
+
+fn advance(position: &mut Position, text: &str) {
+    for c in text.chars() {
+        if c == '\n' {
+            position.line += 1;
+            position.column = 1;
+        } else {
+            position.column += 1;
+        }
+    }
+}
+
+fn is_block_tag(content: &str) -> bool {
+    content.starts_with(['#', '/', '!', '>', '%']) || split_keyword(content).0 == "else"
+}
+
+// Block tags that are alone on their line don't leave an empty line behind, so that
+//
+// {{#each classes}}
+// - {{name}}
+// {{/each}}
+//
+// renders one line per class.
+fn strip_standalone_tags(mut pieces: Vec<Piece>) -> Vec<Piece> {
+    let standalone: Vec<usize> = (0..pieces.len())
+        .filter(|&i| is_standalone(&pieces, i))
+        .collect();
+    for i in standalone {
+        if let Some(Piece::Text(text, _)) = i.checked_sub(1).map(|p| &mut pieces[p]) {
+            let line_start = text.rfind('\n').map_or(0, |n| n + 1);
+            text.truncate(line_start);
+        }
+        if let Some(Piece::Text(text, position)) = pieces.get_mut(i + 1) {
+            let line_end = text.find('\n').map_or(text.len(), |n| n + 1);
+            advance(position, &text[..line_end]);
+            text.replace_range(..line_end, "");
+        }
+    }
+    pieces
+}
+
+fn is_standalone(pieces: &[Piece], i: usize) -> bool {
+    let Piece::Tag { content, .. } = &pieces[i] else {
+        return false;
+    };
+    if !is_block_tag(content) {
+        return false;
+    }
+    let before = match i.checked_sub(1).map(|p| &pieces[p]) {
+        None => Some(""),
+        Some(Piece::Text(text, _)) => text
+            .rfind('\n')
+            .map(|n| &text[n + 1..])
+            .or((i == 1).then_some(text.as_str())),
+        Some(Piece::Tag { .. }) => None,
+    };
+    let after = match pieces.get(i + 1) {
+        None => Some(""),
+        Some(Piece::Text(text, _)) => text
+            .find('\n')
+            .map(|n| &text[..n])
+            .or((i + 2 == pieces.len()).then_some(text.as_str())),
+        Some(Piece::Tag { .. }) => None,
+    };
+    before.is_some_and(|b| b.trim().is_empty()) && after.is_some_and(|a| a.trim().is_empty())
+}
+
+struct NodeParser {
+    pieces: Vec<Piece>,
+    pos: usize,
+    partials: HashMap<String, Partial>,
+    blocks: HashSet<String>,
+    extends: Option<(String, Position)>,
+}
+
+impl NodeParser {
+    // Parses nodes up to the `{{else}}` or closing tag of the block opened at `open`, if any.
+    fn parse_nodes(
+        &mut self,
+        open: Option<(&str, Position)>,
+    ) -> Result<(Vec<Node>, BlockEnd), TemplateError> {
+        let mut nodes = Vec::new();
+        while let Some(piece) = self.pieces.get(self.pos) {
+            self.pos += 1;
+            let (content, position) = match piece {
+                Piece::Text(text, position) => {
+                    nodes.push(Node::Text(text.clone(), *position));
+                    continue;
+                }
+                Piece::Tag { content, position } => (content.clone(), *position),
+            };
+            if content.starts_with('!') {
+                continue;
+            }
+            if let Some(block) = content.strip_prefix('#') {
+                let (keyword, argument) = split_keyword(block);
+                if argument.is_empty() {
+                    return Err(error(
+                        &format!("`{{{{#{}}}}}` needs an argument", keyword),
+                        position,
+                    ));
+                }
+                match keyword {
+                    "each" => nodes.push(self.parse_each(argument, position)?),
+                    "if" => nodes.push(self.parse_if(argument, position)?),
+                    "partial" => self.parse_partial_definition(argument, position)?,
+                    "block" => nodes.push(self.parse_block(argument, position)?),
+                    _ => {
+                        return Err(error(
+                            &format!("unknown block `{{{{#{}}}}}`", keyword),
+                            position,
+                        ))
+                    }
+                }
+                continue;
+            }
+            if let ("else", argument) = split_keyword(&content) {
+                if open.is_none() {
+                    return Err(error("`{{else}}` outside of a block", position));
+                }
+                let condition = match argument.strip_prefix("if") {
+                    Some(condition) if condition.starts_with(char::is_whitespace) => {
+                        Some(Condition::parse(condition, position)?)
+                    }
+                    _ if argument.is_empty() => None,
+                    _ => return Err(error(&format!("invalid `{{{{{}}}}}`", content), position)),
+                };
+                return Ok((nodes, BlockEnd::Else(condition, position)));
+            }
+            if let Some(keyword) = content.strip_prefix('/') {
+                let keyword = keyword.trim();
+                return match open {
+                    Some((expected, _)) if expected == keyword => Ok((nodes, BlockEnd::Close)),
+                    Some((expected, _)) => Err(error(
+                        &format!(
+                            "expected `{{{{/{}}}}}`, found `{{{{/{}}}}}`",
+                            expected, keyword
+                        ),
+                        position,
+                    )),
+                    None => Err(error(
+                        &format!("`{{{{/{}}}}}` without a matching opening tag", keyword),
+                        position,
+                    )),
+                };
+            }
+            if let ("%extends", base) = split_keyword(&content) {
+                if open.is_some() || self.extends.is_some() {
+                    return Err(error(
+                        "`{% extends %}` can only be used once, outside of blocks",
+                        position,
+                    ));
+                }
+                self.extends = Some((base.to_string(), position));
+                continue;
+            }
+            if let Some(name) = content.strip_prefix('>') {
+                nodes.push(Node::Partial {
+                    name: parse_partial_name(name, position)?,
+                    position,
+                });
+                continue;
+            }
+            nodes.push(parse_variable(&content, position)?);
+        }
+        match open {
+            Some((keyword, position)) => Err(error(
+                &format!("`{{{{#{}}}}}` is never closed", keyword),
+                position,
+            )),
+            None => Ok((nodes, BlockEnd::Close)),
+        }
+    }
+
+    fn parse_each(&mut self, argument: &str, position: Position) -> Result<Node, TemplateError> {
+        let open = Some(("each", position));
+        let (body, end) = self.parse_nodes(open)?;
+        let otherwise = match end {
+            BlockEnd::Close => Vec::new(),
+            BlockEnd::Else(None, _) => self.parse_last_branch(open)?,
+            BlockEnd::Else(Some(_), position) => {
+                return Err(error(
+                    "`{{else if}}` can only be used in `{{#if}}` blocks",
+                    position,
+                ))
+            }
+        };
+        let (path, filters) = parse_filtered(argument, position)?;
+        Ok(Node::Each {
+            path,
+            filters,
+            body,
+            otherwise,
+            position,
+        })
+    }
+
+    fn parse_if(&mut self, condition: &str, position: Position) -> Result<Node, TemplateError> {
+        let open = Some(("if", position));
+        let mut branches = Vec::new();
+        let mut condition = Condition::parse(condition, position)?;
+        loop {
+            let (body, end) = self.parse_nodes(open)?;
+            branches.push((condition, body));
+            match end {
+                BlockEnd::Close => {
+                    return Ok(Node::If {
+                        branches,
+                        otherwise: Vec::new(),
+                    })
+                }
+                BlockEnd::Else(Some(next), _) => condition = next,
+                BlockEnd::Else(None, _) => {
+                    return Ok(Node::If {
+                        branches,
+                        otherwise: self.parse_last_branch(open)?,
+                    })
+                }
+            }
+        }
+    }
+
+    fn parse_block(&mut self, name: &str, position: Position) -> Result<Node, TemplateError> {
+        let (body, end) = self.parse_nodes(Some(("block", position)))?;
+        if let BlockEnd::Else(_, position) = end {
+            return Err(error(
+                "`{{else}}` can only be used in `{{#if}}` and `{{#each}}` blocks",
+                position,
+            ));
+        }
+        if !self.blocks.insert(name.to_string()) {
+            return Err(error(
+                &format!("block `{}` is defined more than once", name),
+                position,
+            ));
+        }
+        Ok(Node::Block {
+            name: name.to_string(),
+            body,
+        })
+    }
+
+    fn parse_partial_definition(
+        &mut self,
+        name: &str,
+        position: Position,
+    ) -> Result<(), TemplateError> {
+        let name = parse_partial_name(name, position)?;
+        let (nodes, end) = self.parse_nodes(Some(("partial", position)))?;
+        if let BlockEnd::Else(_, position) = end {
+            return Err(error(
+                "`{{else}}` can only be used in `{{#if}}` and `{{#each}}` blocks",
+                position,
+            ));
+        }
+        if self.partials.contains_key(&name) {
+            return Err(error(
+                &format!("partial `{}` is defined more than once", name),
+                position,
+            ));
+        }
+        self.partials.insert(name, Partial { nodes, file: None });
+        Ok(())
+    }
+
+    // The `{{else}}` branch has to be the last one.
+    fn parse_last_branch(
+        &mut self,
+        open: Option<(&str, Position)>,
+    ) -> Result<Vec<Node>, TemplateError> {
+        match self.parse_nodes(open)? {
+            (nodes, BlockEnd::Close) => Ok(nodes),
+            (_, BlockEnd::Else(_, position)) => {
+                Err(error("nothing can follow the `{{else}}` branch", position))
+            }
+        }
+    }
+}
+
+fn parse_partial_name(name: &str, position: Position) -> Result<String, TemplateError> {
+    let name = name.trim();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(error(&format!("invalid partial name `{}`", name), position));
+    }
+    Ok(name.to_string())
+}
+
+// `path | filter | filter(argument, ...)`
+fn parse_variable(content: &str, position: Position) -> Result<Node, TemplateError> {
+    let (path, filters) = parse_filtered(content, position)?;
+    Ok(Node::Variable {
+        path,
+        filters,
+        position,
+    })
+}
+
+// A path followed by `|`-separated filters, e.g. `summary | truncate(80)`.
+fn parse_filtered(
+    content: &str,
+    position: Position,
+) -> Result<(String, Vec<FilterCall>), TemplateError> {
+    let mut parts = split_top_level(content, '|').into_iter();
+    let path = parts.next().unwrap_or_default().trim();
+    if path.is_empty() || path.contains(char::is_whitespace) {
+        return Err(error(&format!("invalid variable `{}`", content), position));
+    }
+    let filters = parts
+        .map(|filter| parse_filter_call(filter.trim(), position))
+        .collect::<Result<_, _>>()?;
+    Ok((path.to_string(), filters))
+}
+
+fn parse_filter_call(text: &str, position: Position) -> Result<FilterCall, TemplateError> {
+    let (name, arguments) = match text.split_once('(') {
+        Some((name, rest)) => {
+            let Some(arguments) = rest.strip_suffix(')') else {
+                return Err(error(&format!("expected `)` after `{}`", text), position));
+            };
+            let arguments = match arguments.trim() {
+                "" => Vec::new(),
+                arguments => split_top_level(arguments, ',')
+                    .into_iter()
+                    .map(|argument| unquote(argument.trim()).to_string())
+                    .collect(),
+            };
+            (name.trim(), arguments)
+        }
+        None => (text, Vec::new()),
+    };
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(error(&format!("invalid filter `{}`", text), position));
+    }
+    Ok(FilterCall {
+        name: name.to_string(),
+        arguments,
+    })
+}
+
+// Splits at `separator`s that are not inside quotes or parentheses.
+fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            (None, _) if c == separator && depth == 0 => {
+                parts.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+fn unquote(text: &str) -> &str {
+    ['"', '\'']
+        .iter()
+        .find_map(|q| text.strip_prefix(*q).and_then(|t| t.strip_suffix(*q)))
+        .unwrap_or(text)
+}
+
+fn split_keyword(tag: &str) -> (&str, &str) {
+    let tag = tag.trim();
+    tag.split_once(char::is_whitespace)
+        .map_or((tag, ""), |(keyword, argument)| (keyword, argument.trim()))
+}
+
+fn error(message: &str, position: Position) -> TemplateError {
+    TemplateError {
+        message: message.to_string(),
+        file: None,
+        position,
+    }
+}
+
+// All nodes, including the ones inside blocks but not the ones of included partials, with the
+// file each one is from.
+fn walk<'a>(nodes: &'a [Node], file: Option<&'a str>) -> Vec<(&'a Node, Option<&'a str>)> {
+    let mut result = Vec::new();
+    for node in nodes {
+        result.push((node, file));
+        match node {
+            Node::Each {
+                body, otherwise, ..
+            } => {
+                result.extend(walk(body, file));
+                result.extend(walk(otherwise, file));
+            }
+            Node::If {
+                branches,
+                otherwise,
+            } => {
+                for (_, body) in branches {
+                    result.extend(walk(body, file));
+                }
+                result.extend(walk(otherwise, file));
+            }
+            Node::Block { body, .. } => result.extend(walk(body, file)),
+            Node::FromFile { file: inner, nodes } => {
+                result.extend(walk(nodes, inner.as_deref().or(file)))
+            }
+            Node::Text(..) | Node::Variable { .. } | Node::Partial { .. } => {}
+        }
+    }
+    result
+}
+
+fn fill_placeholders(nodes: &mut [Node], values: &BTreeMap<String, String>) {
+    for node in nodes {
+        match node {
+            Node::Text(text, _) => {
+                for (name, value) in values {
+                    *text = text.replace(&format!("[{}]", name), value);
+                }
+            }
+            Node::Each {
+                body, otherwise, ..
+            } => {
+                fill_placeholders(body, values);
+                fill_placeholders(otherwise, values);
+            }
+            Node::If {
+                branches,
+                otherwise,
+            } => {
+                for (_, body) in branches {
+                    fill_placeholders(body, values);
+                }
+                fill_placeholders(otherwise, values);
+            }
+            Node::Block { body: nodes, .. } | Node::FromFile { nodes, .. } => {
+                fill_placeholders(nodes, values)
+            }
+            Node::Variable { .. } | Node::Partial { .. } => {}
+        }
+    }
+}
+
+// Single-line placeholders are lowercase names in brackets, e.g. `[class_name]`. Markdown links
+// and checkboxes such as `[docs](url)`, `[docs][1]`, `[1]: url` and `[x]` are skipped.
+fn find_placeholders(text: &str, mut position: Position) -> Vec<(&str, Position)> {
+    let mut found = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('[') {
+        advance(&mut position, &rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(']') else {
+            break;
+        };
+        let name = &rest[1..end];
+        let is_placeholder = name.len() > 1
+            && name.starts_with(|c: char| c.is_ascii_lowercase())
+            && name.chars().all(|c| c.is_ascii_lowercase() || c == '_')
+            && !rest[end + 1..].starts_with(['(', '[', ':']);
+        if is_placeholder {
+            found.push((name, position));
+        }
+        advance(&mut position, "[");
+        rest = &rest[1..];
+    }
+    found
+}
+
+// The single-line `[if static]` and `[if !static]` conditions in a text, with the word they test
+// and their positions.
+fn find_conditions(text: &str, mut position: Position) -> Vec<(&str, &str, Position)> {
+    let mut found = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("[if ") {
+        advance(&mut position, &rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(']') else {
+            break;
+        };
+        let name = rest[4..end].trim();
+        found.push((&rest[..=end], name.trim_start_matches('!').trim(), position));
+        advance(&mut position, &rest[..=end]);
+        rest = &rest[end + 1..];
+    }
+    found
+}
+
+// The partials included by the nodes, with the file and position of the include.
+fn included_partials(
+    nodes: &[Node],
+    file: Option<&str>,
+) -> Vec<(String, Option<String>, Position)> {
+    walk(nodes, file)
+        .into_iter()
+        .filter_map(|(node, file)| match node {
+            Node::Partial { name, position } => {
+                Some((name.clone(), file.map(String::from), *position))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+// Replaces the bodies of the blocks that are overridden, marking them as coming from `file`.
+fn override_blocks<'a>(
+    nodes: &[Node],
+    overrides: &HashMap<&'a str, &'a Vec<Node>>,
+    file: Option<&str>,
+    used: &mut HashSet<&'a str>,
+) -> Vec<Node> {
+    nodes
+        .iter()
+        .map(|node| match node {
+            Node::Block { name, body } => match overrides.get_key_value(name.as_str()) {
+                Some((key, nodes)) => {
+                    // Blocks nested in the override are for templates extending this one
+                    used.insert(key);
+                    for (node, _) in walk(nodes, None) {
+                        if let Node::Block { name, .. } = node {
+                            used.insert(name);
+                        }
+                    }
+                    Node::Block {
+                        name: name.clone(),
+                        body: vec![Node::FromFile {
+                            file: file.map(String::from),
+                            nodes: (*nodes).clone(),
+                        }],
+                    }
+                }
+                None => Node::Block {
+                    name: name.clone(),
+                    body: override_blocks(body, overrides, file, used),
+                },
+            },
+            Node::Each {
+                path,
+                filters,
+                body,
+                otherwise,
+                position,
+            } => Node::Each {
+                path: path.clone(),
+                filters: filters.clone(),
+                body: override_blocks(body, overrides, file, used),
+                otherwise: override_blocks(otherwise, overrides, file, used),
+                position: *position,
+            },
+            Node::If {
+                branches,
+                otherwise,
+            } => Node::If {
+                branches: branches
+                    .iter()
+                    .map(|(condition, body)| {
+                        (
+                            condition.clone(),
+                            override_blocks(body, overrides, file, used),
+                        )
+                    })
+                    .collect(),
+                otherwise: override_blocks(otherwise, overrides, file, used),
+            },
+            Node::FromFile { file: inner, nodes } => Node::FromFile {
+                file: inner.clone(),
+                nodes: override_blocks(nodes, overrides, file, used),
+            },
+            node => node.clone(),
+        })
+        .collect()
+}
+
+struct RequiredVariables<'a> {
+    prefix: &'a str,
+    partials: &'a HashMap<String, Partial>,
+    // The variables checked with `{{#if}}` around the nodes being collected
+    checked: Vec<&'a str>,
+    required: Vec<(String, Option<String>, Position)>,
+}
+
+impl<'a> RequiredVariables<'a> {
+    fn collect(&mut self, nodes: &'a [Node], file: Option<&'a str>) {
+        for node in nodes {
+            match node {
+                Node::Text(..) => {}
+                Node::Variable { path, position, .. } => {
+                    let Some(name) = path.strip_prefix(self.prefix) else {
+                        continue;
+                    };
+                    if !self.checked.contains(&path.as_str())
+                        && self.required.iter().all(|(n, _, _)| n != name)
+                    {
+                        self.required
+                            .push((name.to_string(), file.map(String::from), *position));
+                    }
+                }
+                Node::Each {
+                    body, otherwise, ..
+                } => {
+                    self.collect(body, file);
+                    self.collect(otherwise, file);
+                }
+                Node::If {
+                    branches,
+                    otherwise,
+                } => {
+                    for (condition, body) in branches {
+                        let is_check = !condition.negated && condition.comparison.is_none();
+                        if is_check {
+                            self.checked.push(&condition.path);
+                        }
+                        self.collect(body, file);
+                        if is_check {
+                            self.checked.pop();
+                        }
+                    }
+                    self.collect(otherwise, file);
+                }
+                Node::Partial { name, .. } => {
+                    if let Some(partial) = self.partials.get(name) {
+                        self.collect(&partial.nodes, partial.file.as_deref().or(file));
+                    }
+                }
+                Node::Block { body, .. } => self.collect(body, file),
+                Node::FromFile { file: inner, nodes } => {
+                    self.collect(nodes, inner.as_deref().or(file))
+                }
+            }
+        }
+    }
+}
+
+// A value being rendered, along with its position when it is an item of an `{{#each}}` loop.
+#[derive(Clone)]
+struct Scope<'a> {
+    value: &'a Value,
+    index: Option<(usize, usize)>,
+}
+
+struct Renderer<'t> {
+    filters: &'t Filters,
+    partials: &'t HashMap<String, Partial>,
+}
+
+impl Renderer<'_> {
+    fn render_nodes<'a>(
+        &self,
+        nodes: &[Node],
+        scopes: &mut Vec<Scope<'a>>,
+        out: &mut String,
+    ) -> Result<(), TemplateError> {
+        for node in nodes {
+            match node {
+                Node::Text(text, _) => out.push_str(text),
+                Node::Variable {
+                    path,
+                    filters,
+                    position,
+                } => {
+                    let value = lookup(path, scopes)
+                        .map(Cow::into_owned)
+                        .unwrap_or_else(|| Value::Text(String::new()));
+                    out.push_str(&self.apply_filters(value, filters, *position)?.render());
+                }
+                Node::Each {
+                    path,
+                    filters,
+                    body,
+                    otherwise,
+                    position,
+                } => {
+                    let mut value = lookup(path, scopes);
+                    if !filters.is_empty() {
+                        let unfiltered = value
+                            .map(Cow::into_owned)
+                            .unwrap_or_else(|| Value::Text(String::new()));
+                        value = Some(Cow::Owned(
+                            self.apply_filters(unfiltered, filters, *position)?,
+                        ));
+                    }
+                    let items = match value.as_deref() {
+                        Some(Value::List(items)) if !items.is_empty() => items,
+                        _ => {
+                            self.render_nodes(otherwise, scopes, out)?;
+                            continue;
+                        }
+                    };
+                    // The items of a filtered list only live as long as this loop
+                    let mut scopes = scopes.clone();
+                    for (i, item) in items.iter().enumerate() {
+                        scopes.push(Scope {
+                            value: item,
+                            index: Some((i, items.len())),
+                        });
+                        self.render_nodes(body, &mut scopes, out)?;
+                        scopes.pop();
+                    }
+                }
+                Node::If {
+                    branches,
+                    otherwise,
+                } => {
+                    let body = branches
+                        .iter()
+                        .find(|(condition, _)| condition.evaluate(scopes))
+                        .map_or(otherwise, |(_, body)| body);
+                    self.render_nodes(body, scopes, out)?;
+                }
+                Node::Partial { name, position } => {
+                    let Some(partial) = self.partials.get(name) else {
+                        return Err(error(&format!("unknown partial `{}`", name), *position));
+                    };
+                    self.render_nodes(&partial.nodes, scopes, out)
+                        .map_err(|e| e.in_file(partial.file.as_deref()))?;
+                }
+                Node::Block { body, .. } => self.render_nodes(body, scopes, out)?,
+                Node::FromFile { file, nodes } => self
+                    .render_nodes(nodes, scopes, out)
+                    .map_err(|e| e.in_file(file.as_deref()))?,
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_filters(
+        &self,
+        mut value: Value,
+        filters: &[FilterCall],
+        position: Position,
+    ) -> Result<Value, TemplateError> {
+        for call in filters {
+            let Some(filter) = self.filters.get(call.name.as_str()) else {
+                return Err(error(&format!("unknown filter `{}`", call.name), position));
+            };
+            value = filter(&value, &call.arguments)
+                .map_err(|message| error(&format!("`{}`: {}", call.name, message), position))?;
+        }
+        Ok(value)
+    }
+}
+
+// Walks a template against a schema instead of rendering it, collecting the problems found.
+struct Checker<'a> {
+    schema: &'a Schema<'a>,
+    partials: &'a HashMap<String, Partial>,
+    errors: Vec<TemplateError>,
+}
+
+impl Checker<'_> {
+    fn check_nodes<'a>(&mut self, nodes: &[Node], scopes: &mut Vec<Scope<'a>>, file: Option<&str>) {
+        for node in nodes {
+            match node {
+                Node::Text(text, position) => self.check_placeholders(text, *position, file),
+                Node::Variable {
+                    path,
+                    filters,
+                    position,
+                } => {
+                    let value = self.check_path(path, *position, scopes, file);
+                    self.check_filters(value, filters, *position, file);
+                }
+                Node::Each {
+                    path,
+                    filters,
+                    body,
+                    otherwise,
+                    position,
+                } => {
+                    let mut value = self.check_path(path, *position, scopes, file);
+                    if !filters.is_empty() {
+                        value = self
+                            .check_filters(value, filters, *position, file)
+                            .map(Cow::Owned);
+                    }
+                    // The body is checked against every item of the list, as the items can
+                    // differ, e.g. the constructs of different kinds
+                    if let Some(Value::List(items)) = value.as_deref() {
+                        for (index, item) in items.iter().enumerate() {
+                            let mut scopes = scopes.clone();
+                            scopes.push(Scope {
+                                value: item,
+                                index: Some((index, items.len())),
+                            });
+                            self.check_nodes(body, &mut scopes, file);
+                        }
+                    }
+                    self.check_nodes(otherwise, scopes, file);
+                }
+                Node::If {
+                    branches,
+                    otherwise,
+                } => {
+                    for (condition, body) in branches {
+                        self.check_path(&condition.path, condition.position, scopes, file);
+                        self.check_nodes(body, scopes, file);
+                    }
+                    self.check_nodes(otherwise, scopes, file);
+                }
+                Node::Partial { name, position } => match self.partials.get(name) {
+                    Some(partial) => {
+                        self.check_nodes(&partial.nodes, scopes, partial.file.as_deref().or(file))
+                    }
+                    None => self.report(format!("unknown partial `{}`", name), *position, file),
+                },
+                Node::Block { body, .. } => self.check_nodes(body, scopes, file),
+                Node::FromFile { file: inner, nodes } => {
+                    self.check_nodes(nodes, scopes, inner.as_deref().or(file))
+                }
+            }
+        }
+    }
+
+    // The filtered value, `None` if a filter is unknown or fails.
+    fn check_filters(
+        &mut self,
+        value: Option<Cow<Value>>,
+        filters: &[FilterCall],
+        position: Position,
+        file: Option<&str>,
+    ) -> Option<Value> {
+        let mut value = value
+            .map(Cow::into_owned)
+            .unwrap_or_else(|| Value::Text(String::new()));
+        for call in filters {
+            let Some(filter) = self.schema.filters.get(call.name.as_str()) else {
+                self.report(format!("unknown filter `{}`", call.name), position, file);
+                return None;
+            };
+            match filter(&value, &call.arguments) {
+                Ok(filtered) => value = filtered,
+                Err(message) => {
+                    self.report(format!("`{}`: {}", call.name, message), position, file);
+                    return None;
+                }
+            }
+        }
+        Some(value)
+    }
+
+    fn check_path<'a>(
+        &mut self,
+        path: &str,
+        position: Position,
+        scopes: &[Scope<'a>],
+        file: Option<&str>,
+    ) -> Option<Cow<'a, Value>> {
+        if path.starts_with(self.schema.user_prefix) {
+            return None;
+        }
+        let value = lookup(path, scopes);
+        if value.is_none() {
+            self.report(format!("unknown variable `{}`", path), position, file);
+        }
+        value
+    }
+
+    fn check_placeholders(&mut self, text: &str, position: Position, file: Option<&str>) {
+        for (name, position) in find_placeholders(text, position) {
+            if !self.schema.placeholders.iter().any(|p| p == name) {
+                self.report(format!("unknown placeholder `[{}]`", name), position, file);
+            }
+        }
+        for (condition, name, position) in find_conditions(text, position) {
+            if !self.schema.conditions.iter().any(|c| c == name) {
+                self.report(format!("unknown condition `{}`", condition), position, file);
+            }
+        }
+    }
+
+    // Reports a problem once, even if the body of a loop has it for every item.
+    fn report(&mut self, message: String, position: Position, file: Option<&str>) {
+        let error = TemplateError {
+            message,
+            file: file.map(String::from),
+            position,
+        };
+        if !self.errors.contains(&error) {
+            self.errors.push(error);
+        }
+    }
+}
+
+// Resolves a dotted path such as `name` or `parent.name` against the innermost scope that has
+// it. `../` skips the innermost scope, `this` is the current item and `@index`, `@number`,
+// `@first` and `@last` describe its position in the loop.
+fn lookup<'a>(path: &str, scopes: &[Scope<'a>]) -> Option<Cow<'a, Value>> {
+    let mut scopes = scopes;
+    let mut path = path.trim();
+    while let Some(rest) = path.strip_prefix("../") {
+        scopes = &scopes[..scopes.len().saturating_sub(1)];
+        path = rest;
+    }
+    if let Some(special) = path.strip_prefix('@') {
+        let (index, len) = scopes.iter().rev().find_map(|scope| scope.index)?;
+        return Some(Cow::Owned(match special {
+            "index" => Value::Text(index.to_string()),
+            "number" => Value::Text((index + 1).to_string()),
+            "first" => Value::Bool(index == 0),
+            "last" => Value::Bool(index + 1 == len),
+            _ => return None,
+        }));
+    }
+
+    let mut segments = path.split('.');
+    let first = segments.next()?;
+    let mut value = if first == "this" {
+        scopes.last()?.value
+    } else {
+        scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.value.get(first))?
+    };
+    for segment in segments {
+        value = value.get(segment)?;
+    }
+    Some(Cow::Borrowed(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str, context: &Value) -> String {
+        Template::parse(template)
+            .unwrap()
+            .render(context, &filters())
+            .unwrap()
+    }
+
+    fn filters() -> Filters {
+        let mut filters = Filters::new();
+        filters.insert("upper", |value, _| Ok(value.render().to_uppercase().into()));
+        filters.insert("join", |value, arguments| match (value, arguments) {
+            (Value::List(items), [separator]) => Ok(items
+                .iter()
+                .map(Value::render)
+                .collect::<Vec<_>>()
+                .join(separator)
+                .into()),
+            (value, [_]) => Ok(value.clone()),
+            _ => Err("expected a separator".to_string()),
+        });
+        filters.insert("reverse", |value, _| match value {
+            Value::List(items) => Ok(Value::List(items.iter().rev().cloned().collect())),
+            value => Ok(value.clone()),
+        });
+        filters
+    }
+
+    fn context() -> Value {
+        let member = |name: &str, params: Vec<&str>| {
+            Value::object([
+                ("name", name.into()),
+                (
+                    "params",
+                    Value::List(
+                        params
+                            .into_iter()
+                            .map(|p| Value::object([("name", p.into())]))
+                            .collect(),
+                    ),
+                ),
+            ])
+        };
+        Value::object([
+            ("title", "Docs".into()),
+            (
+                "classes",
+                Value::List(vec![
+                    Value::object([
+                        ("name", "Logger".into()),
+                        ("base_types", vec!["Base", "ILogger"].into()),
+                        (
+                            "members",
+                            Value::List(vec![
+                                member("Log", vec!["level", "message"]),
+                                member("Flush", vec![]),
+                            ]),
+                        ),
+                    ]),
+                    Value::object([("name", "Sink".into())]),
+                ]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_render_variables() {
+        assert_eq!(
+            render("# {{ title }} {{missing}}{{! comment }}!", &context()),
+            "# Docs !"
+        );
+        assert_eq!(
+            render(
+                "{{#each classes}}{{name}}: {{base_types}}; {{/each}}",
+                &context()
+            ),
+            "Logger: Base, ILogger; Sink: ; "
+        );
+    }
+
+    #[test]
+    fn test_render_nested_loops() {
+        let template = "\
+{{#each classes}}
+## {{name}}
+{{#each members}}
+- {{../name}}.{{name}}({{#each params}}{{name}}{{#each ../params}}{{/each}}{{/each}}) #{{@number}}
+{{/each}}
+{{/each}}
+";
+        assert_eq!(
+            render(template, &context()),
+            "## Logger\n- Logger.Log(levelmessage) #1\n- Logger.Flush() #2\n## Sink\n"
+        );
+        assert_eq!(
+            render(
+                "{{#each classes}}{{#each base_types}}{{this}}{{@last}} {{/each}}{{/each}}",
+                &context()
+            ),
+            "Basefalse ILoggertrue "
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = Template::parse("a\n  {{#each classes}}\n").unwrap_err();
+        assert_eq!(error.position, Position { line: 2, column: 3 });
+        assert_eq!(error.to_string(), "2:3: `{{#each}}` is never closed");
+        let error = Template::parse("{{#each a}}{{/if}}").unwrap_err();
+        assert_eq!(error.message, "expected `{{/each}}`, found `{{/if}}`");
+        assert!(Template::parse("{{/each}}").is_err());
+        assert!(Template::parse("{{name").is_err());
+        assert!(Template::parse("{{#loop x}}{{/loop}}").is_err());
+    }
+
+    #[test]
+    fn test_render_conditions() {
+        let template = "\
+{{#each classes}}
+{{#if members}}
+{{name}} has members{{#if !base_types}} and no base types{{/if}}
+{{else if name == \"Sink\"}}
+{{name}} is a sink
+{{else}}
+{{name}} is empty
+{{/if}}
+{{/each}}
+{{#if classes}}
+Classes: {{classes}}
+{{/if}}
+{{#each enums}}
+- {{name}}
+{{else}}
+No enums{{#if title != Docs}}!{{/if}}
+{{/each}}
+";
+        assert_eq!(
+            render(template, &context()),
+            "Logger has members\nSink is a sink\nClasses: Logger, Sink\nNo enums\n"
+        );
+        assert_eq!(
+            render(
+                "{{#each classes}}{{#if @first}}{{else}}, {{/if}}{{name}}{{/each}}",
+                &context()
+            ),
+            "Logger, Sink"
+        );
+    }
+
+    #[test]
+    fn test_parse_condition_errors() {
+        assert!(Template::parse("{{#if}}{{/if}}").is_err());
+        assert!(Template::parse("{{#if a b}}{{/if}}").is_err());
+        assert!(Template::parse("{{else}}").is_err());
+        let error = Template::parse("{{#if a}}{{else}}\n{{else}}{{/if}}").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "2:1: nothing can follow the `{{else}}` branch"
+        );
+        let error = Template::parse("{{#each a}}{{else if b}}{{/each}}").unwrap_err();
+        assert_eq!(
+            error.message,
+            "`{{else if}}` can only be used in `{{#if}}` blocks"
+        );
+    }
+
+    #[test]
+    fn test_render_filters() {
+        assert_eq!(
+            render(
+                "{{#each classes}}{{name|upper}} {{ base_types | join(\" | \") | upper }};{{/each}}",
+                &context()
+            ),
+            "LOGGER BASE | ILOGGER;SINK ;"
+        );
+        let error = Template::parse("{{title | wrap}}")
+            .unwrap()
+            .render(&context(), &Filters::new())
+            .unwrap_err();
+        assert_eq!(error.to_string(), "1:1: unknown filter `wrap`");
+        assert!(Template::parse("{{title | truncate(80}}").is_err());
+        assert!(Template::parse("{{title | }}").is_err());
+        assert_eq!(
+            split_top_level("a, \"b, c\", (d, e)", ','),
+            vec!["a", " \"b, c\"", " (d, e)"]
+        );
+    }
+
+    #[test]
+    fn test_render_filtered_loops() {
+        assert_eq!(
+            render(
+                "{{#each classes | reverse}}{{@index}} {{name}} {{../title}}; {{/each}}",
+                &context()
+            ),
+            "0 Sink Docs; 1 Logger Docs; "
+        );
+        assert_eq!(
+            render(
+                "{{#each missing | reverse}}{{name}}{{else}}none{{/each}}",
+                &context()
+            ),
+            "none"
+        );
+        let error = Template::parse("{{#each classes | nope}}{{/each}}")
+            .unwrap()
+            .render(&context(), &filters())
+            .unwrap_err();
+        assert_eq!(error.to_string(), "1:1: unknown filter `nope`");
+    }
+
+    #[test]
+    fn test_required_variables() {
+        let template = Template::parse(
+            "{{vars.name}}\n{{#if vars.intro}}{{vars.intro}}{{else}}{{vars.fallback}}{{/if}}\n{{#each a}}{{> entry}}{{/each}}{{name}}\n{{#partial entry}}{{vars.name}} {{vars.author | upper}}{{/partial}}",
+        )
+        .unwrap();
+        let required: Vec<(String, usize, usize)> = template
+            .required_variables("vars.")
+            .into_iter()
+            .map(|(name, _, position)| (name, position.line, position.column))
+            .collect();
+        assert_eq!(
+            required,
+            vec![
+                ("name".to_string(), 1, 1),
+                ("fallback".to_string(), 2, 41),
+                ("author".to_string(), 4, 33),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let mut template = Template::parse(
+            "# [system_name]\n{{#each a}}[name] by [author], see [docs](url){{/each}}\n{{> entry}}\n{{#partial entry}}[author] [x]{{/partial}}",
+        )
+        .unwrap();
+        let placeholders: Vec<(String, usize, usize)> = template
+            .placeholders()
+            .into_iter()
+            .map(|(name, _, position)| (name, position.line, position.column))
+            .collect();
+        assert_eq!(
+            placeholders,
+            vec![
+                ("system_name".to_string(), 1, 3),
+                ("name".to_string(), 2, 12),
+                ("author".to_string(), 2, 22),
+            ]
+        );
+
+        let values = BTreeMap::from([
+            ("system_name".to_string(), "Inventory".to_string()),
+            ("author".to_string(), "Ops".to_string()),
+        ]);
+        template.fill_placeholders(&values);
+        let context = Value::object([(
+            "a",
+            Value::List(vec![Value::object([("name", "[author]".into())])]),
+        )]);
+        assert_eq!(
+            template.render(&context, &Filters::new()).unwrap(),
+            "# Inventory\n[name] by Ops, see [docs](url)\nOps [x]"
+        );
+    }
+
+    #[test]
+    fn test_render_partials() {
+        let template = "\
+{{#partial item}}
+- {{name}}{{#each members}} {{> item}}{{/each}}
+{{/partial}}
+{{#each classes}}
+{{> item}}
+{{/each}}
+";
+        assert!(Template::parse(template)
+            .unwrap_err()
+            .message
+            .contains("item > item"));
+
+        let template = "{{#partial item}}\n- {{name}}\n{{/partial}}\n{{#each classes}}\n  {{> item}}\n{{/each}}\n";
+        assert_eq!(render(template, &context()), "- Logger\n- Sink\n");
+        let error = Template::parse("{{> header}}")
+            .unwrap()
+            .render(&context(), &Filters::new())
+            .unwrap_err();
+        assert_eq!(error.message, "unknown partial `header`");
+        assert!(Template::parse("{{#partial a}}{{/partial}}{{#partial a}}{{/partial}}").is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        let template = "\
+{{#each classes}}
+  {{#if base_types}}
+[class_name] {{@index}} {{../title}} {{vars.name}} [link](url) [ref][1] [x]
+  {{/if}}
+{{#each members}}{{#if is_static}}{{/if}}{{> item}}{{/each}}
+{{/each}}
+{{#partial item}}{{#each params}}{{name | upper | nope}}{{/each}}{{/partial}}
+[nmae] [if !static] [if sttic]";
+        let context = context();
+        let filters = filters();
+        let schema = Schema {
+            context: &context,
+            filters: &filters,
+            user_prefix: "vars.",
+            placeholders: &["class_name".to_string()],
+            conditions: &["static".to_string()],
+        };
+        let problems: Vec<String> = Template::parse(template)
+            .unwrap()
+            .check(&schema)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                "5:18: unknown variable `is_static`",
+                "7:34: unknown filter `nope`",
+                "2:3: unknown variable `base_types`",
+                "5:1: unknown variable `members`",
+                "8:1: unknown placeholder `[nmae]`",
+                "8:21: unknown condition `[if sttic]`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extend_template() {
+        let base = "\
+# {% block title %}API{% endblock %}
+{% block classes %}
+{{#each classes}}
+- {{name}}{% block details %}{% endblock %}
+{{/each}}
+{% endblock %}
+{% block footer %}
+Generated.
+{% endblock %}
+";
+        let base = Template::parse(base).unwrap().with_file("base.md");
+        let child = "\
+{% extends \"base.md\" %}
+{% block title %}Logging{% endblock %}
+{% block details %} ({{members | join(\", \")}}){% endblock %}
+{% block footer %}{% block note %}{% endblock %}{% endblock %}
+";
+        let child = Template::parse(child).unwrap().with_file("child.md");
+        assert_eq!(child.extends().unwrap().0, "base.md");
+        let template = child.extend(base).unwrap();
+        let filters = filters();
+        assert_eq!(
+            template.render(&context(), &filters).unwrap(),
+            "# Logging\n- Logger (Log, Flush)\n- Sink ()\n"
+        );
+
+        let base = Template::parse("{% block body %}{{> missing}}{% endblock %}\n{{x | nope}}")
+            .unwrap()
+            .with_file("base.md");
+        let child =
+            Template::parse("{% extends base.md %}{% block body %}{{y | nope}}{% endblock %}")
+                .unwrap()
+                .with_file("child.md");
+        let error = child
+            .extend(base)
+            .unwrap()
+            .render(&context(), &filters)
+            .unwrap_err();
+        assert_eq!(error.to_string(), "child.md:1:38: unknown filter `nope`");
+
+        let child = Template::parse("{% extends base.md %}\n{% block other %}{% endblock %}")
+            .unwrap()
+            .with_file("child.md");
+        let error = child
+            .extend(Template::parse("{% block body %}{% endblock %}").unwrap())
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "child.md:1:1: block `other` is not in the base template"
+        );
+        assert!(Template::parse("{% block a %}{% endblock %}{% block a %}{% endblock %}").is_err());
+        assert!(Template::parse("{% block a %}{% extends base.md %}{% endblock %}").is_err());
+        assert!(Template::parse("{% include x %}").is_err());
+        assert!(Template::parse("{% block a %}").is_err());
+    }
+}
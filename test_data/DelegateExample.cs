@@ -0,0 +1,6 @@
+/// <summary>
+/// XML documentation comment for a delegate !Comment!. It converts values.
+/// </summary>
+public delegate TResult PublicDelegate<T, TResult>(T input);
+
+internal delegate void InternalDelegate(object sender, string message);
@@ -0,0 +1,19 @@
+namespace Example.Logging
+{
+    /// <summary>
+    /// Logger in the logging namespace !Comment!.
+    /// </summary>
+    public class Logger {
+        // Class body
+    }
+}
+
+namespace Example.Diagnostics
+{
+    /// <summary>
+    /// Logger in the diagnostics namespace !Comment!.
+    /// </summary>
+    public class Logger {
+        // Class body
+    }
+}
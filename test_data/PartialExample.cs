@@ -0,0 +1,8 @@
+using System;
+
+/// <summary>
+/// XML documentation comment for a partial class !Comment!. Only this part is documented.
+/// </summary>
+public partial class PartialClass : IDisposable {
+    public void Dispose() { }
+}
@@ -0,0 +1,32 @@
+namespace Examples.Preprocessor
+{
+    #region Logging
+    /// <summary>
+    /// Writes log messages.
+    /// </summary>
+    public class Logger
+    {
+#if DEBUG
+        /// <summary>
+        /// Writes verbose diagnostics, only available in debug builds.
+        /// </summary>
+        public void Trace(string message) { }
+#endif
+
+        #region Output
+#if UNITY_EDITOR
+        public void Log(string message, UnityEngine.Object context) { }
+#else
+        public void Log(string message) { }
+#endif
+        #endregion
+    }
+    #endregion
+
+#if UNITY_EDITOR
+    /// <summary>
+    /// Editor-only tooling.
+    /// </summary>
+    public static class EditorTools { }
+#endif
+}
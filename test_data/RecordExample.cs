@@ -0,0 +1,15 @@
+/// <summary>
+/// XML documentation comment for a record !Comment!. Positional parameters become properties.
+/// </summary>
+public record PublicRecord(string Name, int Age);
+
+public record class ClassRecord(string Name) : PublicRecord(Name, 0) {
+    // Record body
+}
+
+/// <summary>
+/// XML documentation comment for a record struct !Comment!.
+/// </summary>
+public record struct PublicRecordStruct(int X, int Y);
+
+public readonly record struct ReadonlyRecordStruct(int Width, int Height);